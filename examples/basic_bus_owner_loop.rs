@@ -1,27 +1,45 @@
 use anyhow::{anyhow, Context, Result};
 use bytes::{BufMut, Bytes, BytesMut};
 use smbus_pec::pec;
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::io;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::net::UdpSocket;
 use tokio::sync::mpsc::Sender;
 use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
 
 use mctp_base_lib::{
     base::*,
     control::{
         allocate_eids::AllocationStatus,
-        enums::{CommandCode, CompletionCode, MessageType},
+        enums::{
+            CommandCode, CompletionCode, MessageType, PhysicalMediumIdentifier,
+            PhysicalTransportBinding, RoutingEntryType,
+        },
         get_eid::{EidType, EndpointType},
+        get_routing_table::RoutingTableEntry,
+        get_vendor_message_support::{VendorCapabilitySet, VendorId},
+        get_version_support::{VersionEntry, MCTP_BASE_SPEC_MSG_TYPE, MCTP_BASE_SPEC_VERSION},
         models::ControlMsgHeader,
+        routing_info_update::RoutingInfoEntry,
         set_eid::EidAllocationStatus,
         ControlMsgReponseStatus, *,
     },
 };
-use mctp_emu::{endpoint::MsgFlowTag, hex_dump::print_buf, OneshotResponder};
+use mctp_emu::{
+    endpoint::{simple::EndpointDiscoveryState, MsgFlowTag},
+    hex_dump::print_buf,
+    OneshotResponder,
+};
+
+/// The emulated endpoint only has a single SMBus binding
+const SMBUS_BINDING_ID: u64 = 1;
+
+/// Baseline transmission unit, used to size paged responses
+const BASELINE_MTU: usize = 64;
 
 pub type MctpFlow = (MsgFlowTag, OneshotResponder<Bytes>);
 pub type MctpFlowList = Vec<MctpFlow>;
@@ -43,25 +61,37 @@ enum PhysicalTransportCommands {
 struct MctpEndpointContext {
     smbus_addr: u8,
     msg_types: Vec<u8>,
+    msg_type_versions: HashMap<u8, Vec<VersionEntry>>,
+    vendor_capability_sets: Vec<VendorCapabilitySet>,
     topmost_bus_owner: bool,
     assigned_eid: AtomicU8,
     perform_discovery: Arc<AtomicBool>,
+    dest_smbus_addr: u8,
     min_eid_in_pool: u8,
     eid_pool_size: u8,
     next_msg_tag: AtomicU8,
     next_instance_id: AtomicU8,
+    uuid: Uuid,
+    /// Identity of the network the emulated endpoint is attached to
+    network_id: Uuid,
+    discovery: EndpointDiscoveryState,
+    discovered_endpoints: Mutex<HashMap<u8, EndpointContext>>,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
 #[allow(non_camel_case_types, unused)]
 struct EndpointContext {
     eid: u8,
+    smbus_addr: u8,
     endpoint_type: EndpointType,
     eid_type: EidType,
     eid_allocation_status: EidAllocationStatus,
     min_dynamic_eid: u8,
     max_dynamic_eid: u8,
     dynamic_eid_pool_size: u8,
+    msg_types: Vec<u8>,
+    vendor_capability_sets: Vec<VendorCapabilitySet>,
+    uuid: Uuid,
 }
 
 impl MctpEndpointContext {
@@ -87,12 +117,16 @@ impl MctpEndpointContext {
                     .context("GetEID Response is non-successful")?;
                 endpoint = EndpointContext {
                     eid: resp.eid,
-                    endpoint_type: EndpointType::from(resp.endpoint_type()),
-                    eid_type: EidType::from(resp.eid_type()),
+                    smbus_addr: self.dest_smbus_addr,
+                    endpoint_type: resp.endpoint_type(),
+                    eid_type: resp.eid_type(),
                     eid_allocation_status: EidAllocationStatus::NoPoolSupport,
                     min_dynamic_eid: 0,
                     max_dynamic_eid: 0,
                     dynamic_eid_pool_size: 0,
+                    msg_types: Vec::new(),
+                    vendor_capability_sets: Vec::new(),
+                    uuid: Uuid::nil(),
                 };
 
                 println!("Discovered endpoint: {:#?}", resp);
@@ -110,9 +144,10 @@ impl MctpEndpointContext {
                         .context("SetEid request failed")?;
                     resp.is_success()?;
 
+                    endpoint.eid = resp.eid_setting;
                     endpoint.eid_allocation_status = resp.eid_allocation_status();
                     // endpoint.eid_assignment_status
-                    if resp.eid_assignment_status() != 0 {
+                    if resp.eid_assignment_status() != EidAssignmentStatus::Accepted {
                         return Err(anyhow!("EID assignment was rejected: {:#?}", resp));
                     }
                     if endpoint.endpoint_type == EndpointType::BusOwnerOrBridge
@@ -145,13 +180,72 @@ impl MctpEndpointContext {
                     if resp.allocation_status() != AllocationStatus::AllocationAccepted {
                         return Err(anyhow!("EID pool allocation was rejected: {:#?}", resp));
                     }
+
+                    // Let the bridge know how to reach the endpoints discovered so far
+                    let entries = self.routing_info_entries()?;
+                    if !entries.is_empty() {
+                        let resp = self
+                            .send_routing_info_update_request(endpoint.eid, entries, tx_cn.clone())
+                            .await
+                            .context("RoutingInformationUpdate request failed")?;
+                        resp.is_success()
+                            .context("RoutingInformationUpdate Response is non-successful")?;
+                    }
                 }
 
-                // TODO: Step 4: Get Msg Types Supported
+                // Step 4: Get Msg Types Supported
+                let resp = self
+                    .send_get_message_type_support_request(endpoint.eid, tx_cn.clone())
+                    .await
+                    .context("GetMessageTypeSupport request failed")?;
+                resp.is_success()
+                    .context("GetMessageTypeSupport Response is non-successful")?;
+                endpoint.msg_types = resp.message_types;
+                println!(
+                    "Endpoint {:?} supports msg types: {:?}",
+                    endpoint.eid, endpoint.msg_types
+                );
 
-                // TODO: Step 5: get Vendor Defined Msg Types Supported
+                // Step 5: get Vendor Defined Msg Types Supported
+                if endpoint.msg_types.iter().any(|msg_type| {
+                    *msg_type == MessageType::VendorDefinedPCI as u8
+                        || *msg_type == MessageType::VendorDefinedIANA as u8
+                }) {
+                    let mut selector = 0;
+                    while selector != get_vendor_message_support::NO_MORE_SETS {
+                        let resp = self
+                            .send_get_vendor_message_support_request(
+                                endpoint.eid,
+                                selector,
+                                tx_cn.clone(),
+                            )
+                            .await
+                            .context("GetVendorDefinedMessageSupport request failed")?;
+                        resp.is_success()
+                            .context("GetVendorDefinedMessageSupport Response is non-successful")?;
+                        endpoint.vendor_capability_sets.push(resp.capability_set);
+                        selector = resp.vendor_id_set_selector;
+                    }
+                    println!(
+                        "Endpoint {:?} supports vendor capability sets: {:?}",
+                        endpoint.eid, endpoint.vendor_capability_sets
+                    );
+                }
 
-                // TODO: Step 6: Get UUID
+                // Step 6: Get UUID
+                let resp = self
+                    .send_get_uuid_request(endpoint.eid, tx_cn.clone())
+                    .await
+                    .context("GetEndpointUUID request failed")?;
+                resp.is_success()
+                    .context("GetEndpointUUID Response is non-successful")?;
+                endpoint.uuid = resp.uuid();
+                println!("Endpoint {:?} has UUID: {}", endpoint.eid, endpoint.uuid);
+
+                self.discovered_endpoints
+                    .lock()
+                    .unwrap()
+                    .insert(endpoint.eid, endpoint);
             } else {
                 tokio::time::sleep(Duration::from_secs(5)).await;
 
@@ -253,6 +347,162 @@ impl MctpEndpointContext {
         Ok(response)
     }
 
+    async fn send_routing_info_update_request(
+        &self,
+        dest_eid: u8,
+        entries: Vec<RoutingInfoEntry>,
+        tx_cn: Sender<PhysicalTransportCommands>,
+    ) -> Result<routing_info_update::Response> {
+        use routing_info_update::*;
+
+        let transport_hdr = self.new_transport_hdr(dest_eid);
+        let ctrl_hdr = ControlMsgHeader::new(
+            CommandCode::RoutingInformationUpdate,
+            self.next_instance_id.fetch_add(1, Ordering::SeqCst),
+            false,
+            true,
+            false,
+        );
+        let req = Request::new(ctrl_hdr, entries)?;
+        let req_payload = ControlPayload::new(transport_hdr, ctrl_hdr, req);
+        let bytes = Bytes::from(req_payload);
+
+        let (_, response) = self
+            .send_and_decode::<Response>(bytes.clone(), MessageType::Control, tx_cn)
+            .await
+            .context("Failed sending RoutingInformationUpdate request")?;
+        Ok(response)
+    }
+
+    /// Routing entries describing every endpoint (and bridged EID pool) discovered so far
+    fn routing_info_entries(&self) -> Result<Vec<RoutingInfoEntry>> {
+        let discovered_endpoints = self.discovered_endpoints.lock().unwrap();
+        let mut entries = Vec::new();
+        for endpoint in discovered_endpoints.values() {
+            let phy_addr = vec![endpoint.smbus_addr];
+            if endpoint.dynamic_eid_pool_size == 0 {
+                entries.push(RoutingInfoEntry::new(
+                    RoutingEntryType::SingleEndpoint,
+                    endpoint.eid,
+                    1,
+                    phy_addr,
+                )?);
+                continue;
+            }
+            entries.push(RoutingInfoEntry::new(
+                RoutingEntryType::SingleBridge,
+                endpoint.eid,
+                1,
+                phy_addr.clone(),
+            )?);
+            entries.push(RoutingInfoEntry::new(
+                RoutingEntryType::AdditionalBridgeEids,
+                endpoint.min_dynamic_eid,
+                endpoint.dynamic_eid_pool_size,
+                phy_addr,
+            )?);
+        }
+        Ok(entries)
+    }
+
+    /// Routing table reported to the BMC, built from the endpoints discovered on the SMBus
+    fn routing_table_entries(&self) -> Result<Vec<RoutingTableEntry>> {
+        let entries = self
+            .routing_info_entries()?
+            .into_iter()
+            .map(|entry| {
+                RoutingTableEntry::new(
+                    entry.eid_range_size,
+                    entry.starting_eid,
+                    entry.entry_type,
+                    false,
+                    0,
+                    PhysicalTransportBinding::MCTPoverSMBus,
+                    PhysicalMediumIdentifier::SMBUS_2_0_100khz,
+                    entry.physical_address,
+                )
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(entries)
+    }
+
+    async fn send_get_message_type_support_request(
+        &self,
+        dest_eid: u8,
+        tx_cn: Sender<PhysicalTransportCommands>,
+    ) -> Result<get_message_type_support::Response> {
+        use get_message_type_support::*;
+
+        let transport_hdr = self.new_transport_hdr(dest_eid);
+        let ctrl_hdr = ControlMsgHeader::new(
+            CommandCode::GetMessageTypeSupport,
+            self.next_instance_id.fetch_add(1, Ordering::SeqCst),
+            false,
+            true,
+            false,
+        );
+        let req = Request { hdr: ctrl_hdr };
+        let req_payload = ControlPayload::new(transport_hdr, ctrl_hdr, req);
+        let bytes = Bytes::from(req_payload);
+
+        let (_, response) = self
+            .send_and_decode::<Response>(bytes.clone(), MessageType::Control, tx_cn)
+            .await
+            .context("Failed sending GetMessageTypeSupport request")?;
+        Ok(response)
+    }
+
+    async fn send_get_vendor_message_support_request(
+        &self,
+        dest_eid: u8,
+        vendor_id_set_selector: u8,
+        tx_cn: Sender<PhysicalTransportCommands>,
+    ) -> Result<get_vendor_message_support::Response> {
+        use get_vendor_message_support::*;
+
+        let transport_hdr = self.new_transport_hdr(dest_eid);
+        let ctrl_hdr = ControlMsgHeader::new(
+            CommandCode::GetVendorDefinedMessageSupport,
+            self.next_instance_id.fetch_add(1, Ordering::SeqCst),
+            false,
+            true,
+            false,
+        );
+        let req = Request::new(ctrl_hdr, vendor_id_set_selector);
+        let req_payload = ControlPayload::new(transport_hdr, ctrl_hdr, req);
+        let bytes = Bytes::from(req_payload);
+
+        let (_, response) = self
+            .send_and_decode::<Response>(bytes.clone(), MessageType::Control, tx_cn)
+            .await
+            .context("Failed sending GetVendorDefinedMessageSupport request")?;
+        Ok(response)
+    }
+
+    async fn send_get_uuid_request(
+        &self,
+        dest_eid: u8,
+        tx_cn: Sender<PhysicalTransportCommands>,
+    ) -> Result<get_uuid::Response> {
+        let transport_hdr = self.new_transport_hdr(dest_eid);
+        let ctrl_hdr = ControlMsgHeader::new(
+            CommandCode::GetEndpointUUID,
+            self.next_instance_id.fetch_add(1, Ordering::SeqCst),
+            false,
+            true,
+            false,
+        );
+        let req = get_uuid::Request { hdr: ctrl_hdr };
+        let req_payload = ControlPayload::new(transport_hdr, ctrl_hdr, req);
+        let bytes = Bytes::from(req_payload);
+
+        let (_, response) = self
+            .send_and_decode::<get_uuid::Response>(bytes.clone(), MessageType::Control, tx_cn)
+            .await
+            .context("Failed sending GetEndpointUUID request")?;
+        Ok(response)
+    }
+
     fn new_transport_hdr(&self, dst_eid: u8) -> TransportHeader {
         TransportHeader::builder()
             .src_eid(self.assigned_eid.load(Ordering::SeqCst))
@@ -264,7 +514,7 @@ impl MctpEndpointContext {
             .build()
     }
 
-    async fn send_and_decode<T: TryFrom<Bytes>>(
+    async fn send_and_decode<T: TryFrom<Bytes, Error = MctpBaseLibError>>(
         &self,
         bytes: Bytes,
         msg_type: MessageType,
@@ -285,17 +535,28 @@ impl MctpEndpointContext {
         let res_bytes = resp_rx.await.context("Failed waiting for result")?.unwrap();
         println!("GOT = {:?}", res_bytes);
 
-        ControlPayload::try_to_response::<T>(res_bytes)
+        Ok(ControlPayload::try_to_response::<T>(res_bytes)?)
     }
 
-    pub fn handle_request(&self, bytes: Bytes) -> Result<Bytes> {
+    /// Handles a request received from the bus owner. Returns `None` for requests that don't get a
+    /// response.
+    pub fn handle_request(&self, bytes: Bytes) -> Result<Option<Bytes>> {
         let payload = ControlPayload::try_from(bytes).context("Failed parsing payload")?;
-        match payload.command_code() {
-            Ok(CommandCode::SetEndpointID) => {
+        let req = match ControlMessage::try_from(&payload) {
+            Ok(ControlMessage::Request(req)) => req,
+            Ok(ControlMessage::Response(_)) | Err(MctpBaseLibError::UnsupportedCommand { .. }) => {
+                return self.unsupported_response(&payload)
+            }
+            Err(err) => return Err(err).context("Failed parsing control request"),
+        };
+        match req {
+            ControlRequest::SetEndpointID(req) => {
                 use set_eid::*;
-                let req = Request::try_from(payload.clone())
-                    .context("Failed parsing SetEndpointID msg")?;
-                self.assigned_eid.store(req.eid, Ordering::SeqCst);
+
+                if req.operation() != Operation::SetDiscoveredFlag {
+                    self.assigned_eid.store(req.eid, Ordering::SeqCst);
+                }
+                self.discovery.set_discovered(SMBUS_BINDING_ID);
                 let resp = Response::from(
                     req,
                     CompletionCode::Success,
@@ -309,13 +570,11 @@ impl MctpEndpointContext {
                 println!("DEBUG: GetEid response: {:#?}", resp);
                 println!("DEBUG: GetEid response bytes: {:#?}", resp_bytes);
                 print_buf(resp_bytes.clone());
-                Ok(resp_bytes)
+                Ok(Some(resp_bytes))
             }
-            Ok(CommandCode::GetEndpointID) => {
+            ControlRequest::GetEndpointID(req) => {
                 use get_eid::*;
 
-                let req =
-                    Request::try_from(payload.clone()).context("Failed parsing GetEid msg")?;
                 println!("DEBUG: GetEid request: {:#?}", req);
 
                 let resp = Response::from(
@@ -331,11 +590,139 @@ impl MctpEndpointContext {
                 println!("DEBUG: GetEid response: {:#?}", resp);
                 println!("DEBUG: GetEid response bytes: {:#?}", resp_bytes);
                 print_buf(resp_bytes.clone());
-                Ok(resp_bytes)
+                Ok(Some(resp_bytes))
             }
-            Ok(CommandCode::DiscoveryNotify) => {
-                let _req =
-                    EmptyRequest::try_from(payload.clone()).context("Failed parsing GetEid msg")?;
+            ControlRequest::GetEndpointUUID(req) => {
+                use get_uuid::*;
+
+                let resp = Response::from(req, CompletionCode::Success, self.uuid);
+                let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+                let resp_bytes = Bytes::from(resp_payload);
+                println!("DEBUG: GetEndpointUUID response: {:#?}", resp);
+                print_buf(resp_bytes.clone());
+                Ok(Some(resp_bytes))
+            }
+            ControlRequest::GetNetworkID(req) => {
+                use get_network_id::*;
+
+                let resp = Response::from(req, CompletionCode::Success, self.network_id);
+                let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+                let resp_bytes = Bytes::from(resp_payload);
+                println!("DEBUG: GetNetworkID response: {:#?}", resp);
+                print_buf(resp_bytes.clone());
+                Ok(Some(resp_bytes))
+            }
+            ControlRequest::QuerySupportedInterfaces(req) => {
+                use query_supported_interfaces::*;
+
+                let interfaces = vec![SupportedInterface::new(
+                    PhysicalTransportBinding::MCTPoverSMBus,
+                    PhysicalMediumIdentifier::SMBUS_2_0_100khz,
+                    self.assigned_eid.load(Ordering::SeqCst),
+                )];
+                let resp = Response::from(req, CompletionCode::Success, interfaces)?;
+                println!("DEBUG: QuerySupportedInterfaces response: {:#?}", resp);
+                let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+                let resp_bytes = Bytes::from(resp_payload);
+                print_buf(resp_bytes.clone());
+                Ok(Some(resp_bytes))
+            }
+            ControlRequest::ResolveUUID(req) => {
+                use resolve_uuid::*;
+
+                let resolved: Vec<_> = {
+                    let discovered_endpoints = self.discovered_endpoints.lock().unwrap();
+                    let mut endpoints: Vec<_> = discovered_endpoints
+                        .values()
+                        .filter(|endpoint| endpoint.uuid == req.uuid())
+                        .collect();
+                    endpoints.sort_by_key(|endpoint| endpoint.eid);
+                    endpoints
+                        .iter()
+                        .map(|endpoint| {
+                            UuidResolutionEntry::new(endpoint.eid, vec![endpoint.smbus_addr])
+                        })
+                        .collect::<std::result::Result<_, _>>()?
+                };
+                let resp = Response::from_entries(req, &resolved, BASELINE_MTU);
+                println!("DEBUG: ResolveUUID response: {:#?}", resp);
+                let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+                let resp_bytes = Bytes::from(resp_payload);
+                print_buf(resp_bytes.clone());
+                Ok(Some(resp_bytes))
+            }
+            ControlRequest::GetMessageTypeSupport(req) => {
+                use get_message_type_support::*;
+
+                let resp = Response::from(req, CompletionCode::Success, self.msg_types.clone())?;
+                println!("DEBUG: GetMessageTypeSupport response: {:#?}", resp);
+                let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+                let resp_bytes = Bytes::from(resp_payload);
+                print_buf(resp_bytes.clone());
+                Ok(Some(resp_bytes))
+            }
+            ControlRequest::GetVendorDefinedMessageSupport(req) => {
+                use get_vendor_message_support::*;
+
+                let selector = req.vendor_id_set_selector as usize;
+                let resp = match self.vendor_capability_sets.get(selector) {
+                    Some(set) => {
+                        let next_selector = if selector + 1 < self.vendor_capability_sets.len() {
+                            (selector + 1) as u8
+                        } else {
+                            NO_MORE_SETS
+                        };
+                        Response::from(req, CompletionCode::Success, next_selector, *set)
+                    }
+                    None => Response::from(
+                        req,
+                        CompletionCode::ErrorInvalidData,
+                        NO_MORE_SETS,
+                        VendorCapabilitySet::default(),
+                    ),
+                };
+                println!(
+                    "DEBUG: GetVendorDefinedMessageSupport response: {:#?}",
+                    resp
+                );
+                let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+                let resp_bytes = Bytes::from(resp_payload);
+                print_buf(resp_bytes.clone());
+                Ok(Some(resp_bytes))
+            }
+            ControlRequest::GetMCTPVersionSupport(req) => {
+                use get_version_support::*;
+
+                let resp = match self.msg_type_versions.get(&req.message_type_number) {
+                    Some(entries) => Response::from(req, CompletionCode::Success, entries.clone())?,
+                    None => Response::message_type_not_supported(req),
+                };
+                println!("DEBUG: GetMCTPVersionSupport response: {:#?}", resp);
+                let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+                let resp_bytes = Bytes::from(resp_payload);
+                print_buf(resp_bytes.clone());
+                Ok(Some(resp_bytes))
+            }
+            ControlRequest::PrepareForEndpointDiscovery(_) => {
+                let resp = self
+                    .discovery
+                    .handle_prepare_for_discovery(SMBUS_BINDING_ID, payload.clone())
+                    .context("Failed handling PrepareForEndpointDiscovery msg")?;
+                let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+                let resp_bytes = Bytes::from(resp_payload);
+                print_buf(resp_bytes.clone());
+                Ok(Some(resp_bytes))
+            }
+            ControlRequest::EndpointDiscovery(_) => {
+                let resp = self
+                    .discovery
+                    .handle_endpoint_discovery(SMBUS_BINDING_ID, payload.clone())
+                    .context("Failed handling EndpointDiscovery msg")?;
+                Ok(resp.map(|resp| {
+                    Bytes::from(payload.create_response_payload(resp.hdr, resp.into()))
+                }))
+            }
+            ControlRequest::DiscoveryNotify(_req) => {
                 let resp = EmptyResponse::from(_req, CompletionCode::Success);
                 let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
                 let resp_bytes = Bytes::from(resp_payload);
@@ -345,33 +732,36 @@ impl MctpEndpointContext {
 
                 println!("DEBUG: DiscoveryNotify request: {:#?}", _req);
                 self.perform_discovery.store(true, Ordering::SeqCst);
-                Ok(resp_bytes)
+                Ok(Some(resp_bytes))
             }
-            Ok(CommandCode::GetRoutingTableEntries) => {
+            ControlRequest::GetRoutingTableEntries(req) => {
                 use get_routing_table::*;
-                let req = Request::try_from(payload.clone())
-                    .context("Failed parsing GetRoutingTable msg")?;
-                let resp = Response::from(req, CompletionCode::Success, 0xff, 0);
-                let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+
+                let table = self.routing_table_entries()?;
+                let resp = Response::from_table(req, &table, BASELINE_MTU);
+                let resp_payload = payload.create_response_payload(resp.hdr, resp.clone().into());
                 let resp_bytes = Bytes::from(resp_payload);
-                println!("DEBUG: GetEid response: {:#?}", resp);
-                println!("DEBUG: GetEid response bytes: {:#?}", resp_bytes);
+                println!("DEBUG: GetRoutingTableEntries response: {:#?}", resp);
+                println!(
+                    "DEBUG: GetRoutingTableEntries response bytes: {:#?}",
+                    resp_bytes
+                );
                 print_buf(resp_bytes.clone());
-                Ok(resp_bytes)
-            }
-            Ok(_) => {
-                let _req =
-                    EmptyRequest::try_from(payload.clone()).context("Failed parsing GetEid msg")?;
-                let resp = EmptyResponse::from(_req, CompletionCode::ErrorUnsupportedCmd);
-                let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
-                let resp_bytes = Bytes::from(resp_payload);
-
-                println!("Unsupported command: {:?}", _req);
-                Ok(resp_bytes)
+                Ok(Some(resp_bytes))
             }
-            Err(_) => todo!(),
+            _ => self.unsupported_response(&payload),
         }
     }
+
+    fn unsupported_response(&self, payload: &ControlPayload) -> Result<Option<Bytes>> {
+        let _req = EmptyRequest::try_from(payload.clone()).context("Failed parsing request")?;
+        let resp = EmptyResponse::from(_req, CompletionCode::ErrorUnsupportedCmd);
+        let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+        let resp_bytes = Bytes::from(resp_payload);
+
+        println!("Unsupported command: {:?}", _req);
+        Ok(Some(resp_bytes))
+    }
 }
 
 #[tokio::main]
@@ -387,7 +777,17 @@ async fn main() -> Result<()> {
     const DEST_SMBUS_ADDR_7BIT: u8 = 0x60 >> 1;
     let ctx = Arc::new(MctpEndpointContext {
         smbus_addr: SMBUS_ADDR_7BIT,
-        msg_types: vec![0x7E],
+        dest_smbus_addr: DEST_SMBUS_ADDR_7BIT,
+        msg_types: vec![0x7E, 0x7F],
+        vendor_capability_sets: vec![
+            VendorCapabilitySet::new(VendorId::Pci(0x10de), 0x0001),
+            VendorCapabilitySet::new(VendorId::Pci(0x10de), 0x0002),
+            VendorCapabilitySet::new(VendorId::Iana(0x0000_1234), 0x0100),
+        ],
+        msg_type_versions: HashMap::from([
+            (MCTP_BASE_SPEC_MSG_TYPE, vec![MCTP_BASE_SPEC_VERSION]),
+            (MessageType::Control as u8, vec![MCTP_BASE_SPEC_VERSION]),
+        ]),
         topmost_bus_owner: false,
         assigned_eid: AtomicU8::new(0x00),
         perform_discovery: perform_discovery.clone(),
@@ -395,6 +795,10 @@ async fn main() -> Result<()> {
         min_eid_in_pool: 0x80,
         next_instance_id: AtomicU8::new(1),
         next_msg_tag: AtomicU8::new(1),
+        uuid: Uuid::new_v4(),
+        network_id: Uuid::new_v4(),
+        discovery: EndpointDiscoveryState::new(),
+        discovered_endpoints: Default::default(),
     });
 
     let (mctp_cn_tx, mut mctp_cn_rx) = mpsc::channel::<PhysicalTransportCommands>(32);
@@ -540,11 +944,11 @@ async fn main() -> Result<()> {
                         }
                     }
 
-                    let send_cmd_closure = send_cmd_closure;
                     match ctx2.handle_request(buf.slice(4..)) {
-                        Ok(resp) => {
+                        Ok(Some(resp)) => {
                             send_cmd_closure(MessageType::Control, resp, None).await;
                         }
+                        Ok(None) => {}
                         Err(err) => {
                             println!("Failed handling request: {:?}", err);
                         }
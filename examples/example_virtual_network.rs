@@ -1,7 +1,7 @@
 use anyhow::Result;
 use bytes::Bytes;
 use mctp_emu::network::virtual_network::VirtualNetwork;
-use mctp_emu::network::SockAddrMctp;
+use mctp_emu::network::SocketAddress;
 
 use mctp_emu::phys::smbus_netdev::SmbusNetDevBinding;
 
@@ -30,9 +30,14 @@ async fn main() -> Result<()> {
     tracing::warn!("Engines are running...");
 
     // Set local endpoint address and listen for data
-    let addr = SockAddrMctp::default();
+    let addr = SocketAddress::Extended {
+        address: 0,
+        network: 1,
+        binding_id: 0,
+        phy_addr: 0x25,
+    };
     let sd = network1.socket();
-    network1.bind(sd, addr)?;
+    network1.bind(sd, 0, 0, 0)?;
 
     network1
         .sendto(sd, Bytes::from(vec![0, 1, 2, 3]), addr)
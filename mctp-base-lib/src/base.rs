@@ -5,7 +5,8 @@ mod traits;
 
 pub use self::errors::*;
 pub use self::libc::{stddef_h::*, stdint_intn_h::*, stdint_uintn_h::*, types_h::*};
-pub use self::models::TransportHeader;
+pub use self::models::{TransportHeader, MCTP_BASE_PROTOCOL_SUPPORTED_HDR_VERSION};
+pub use self::traits::*;
 
 pub use anyhow::Context;
 pub use bytes::Bytes;
@@ -1,5 +1,10 @@
 use thiserror::Error;
 
+use crate::control::{
+    enums::{CommandCode, CompletionCode, ResponseCode},
+    validation::ProtocolViolation,
+};
+
 #[derive(Error, Debug)]
 pub enum MctpBaseLibError {
     #[error("invalid payload size (found {found:?}, expected {expected:?})")]
@@ -8,10 +13,37 @@ pub enum MctpBaseLibError {
     #[error("unknown value ({value:?})")]
     UnknownValue { value: String },
 
+    #[error("unsupported control command ({command:?})")]
+    UnsupportedCommand { command: CommandCode },
+
+    #[error("{command:?} failed with {completion_code}")]
+    ErrorResponse {
+        command: CommandCode,
+        completion_code: ResponseCode,
+    },
+
+    #[error("protocol violation: {0}")]
+    ProtocolViolation(#[from] ProtocolViolation),
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
 
+impl MctpBaseLibError {
+    /// Completion code of the response to a request that failed with this error
+    pub fn completion_code(&self) -> CompletionCode {
+        match self {
+            Self::InvalidPayloadSize { .. } => CompletionCode::ErrorInvalidLength,
+            Self::UnknownValue { .. } => CompletionCode::ErrorInvalidData,
+            Self::UnsupportedCommand { .. } => CompletionCode::ErrorUnsupportedCmd,
+            Self::ProtocolViolation(violation) => violation.completion_code(),
+            // a failure reported by another endpoint isn't a problem with the request
+            Self::ErrorResponse { .. } => CompletionCode::Error,
+            Self::Other(_) => CompletionCode::Error,
+        }
+    }
+}
+
 /// Result type used when return value is needed from methods in library.
 pub type MctpBaseLibResult<T> = std::result::Result<T, MctpBaseLibError>;
 
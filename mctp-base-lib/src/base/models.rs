@@ -1,12 +1,12 @@
 use c2rust_bitfields::BitfieldStruct;
 use cascade::cascade;
-use mctp_emu_derive::{add_binary_derives, FromBinary};
+use mctp_emu_derive::{add_binary_derives, BitfieldView, FromBinary};
 
 use crate::base::*;
 
 pub const MCTP_BASE_PROTOCOL_SUPPORTED_HDR_VERSION: uint8_t = 0x1;
 
-#[derive(Copy, Clone, BitfieldStruct, Debug, PartialEq, Eq, Default)]
+#[derive(Copy, Clone, BitfieldStruct, BitfieldView, PartialEq, Eq, Default)]
 #[add_binary_derives]
 #[repr(C, packed)]
 pub struct TransportHeader {
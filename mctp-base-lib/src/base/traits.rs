@@ -1 +1,129 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::mem;
 
+use crate::base::{MctpBaseLibError, MctpBaseLibResult};
+
+/// Reads and writes a value at the current position of a message buffer.
+///
+/// This is used for the parts of a message that can't be described by a fixed size structure, e.g.
+/// count-prefixed lists of entries at the end of a control message.
+pub trait WireCodec: Sized {
+    /// Decodes a value from the front of `buf`, advancing it past the consumed bytes.
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self>;
+
+    /// Appends the wire representation of the value to `buf`.
+    fn encode_into(&self, buf: &mut BytesMut);
+}
+
+impl WireCodec for u8 {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        ensure_remaining(buf, 1)?;
+        Ok(buf.get_u8())
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u8(*self);
+    }
+}
+
+/// Multi-byte fields of control messages are sent most significant byte first
+impl WireCodec for u16 {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        ensure_remaining(buf, 2)?;
+        Ok(buf.get_u16())
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u16(*self);
+    }
+}
+
+impl WireCodec for u32 {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        ensure_remaining(buf, 4)?;
+        Ok(buf.get_u32())
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u32(*self);
+    }
+}
+
+impl WireCodec for u64 {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        ensure_remaining(buf, 8)?;
+        Ok(buf.get_u64())
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u64(*self);
+    }
+}
+
+/// Byte arrays (e.g. UUIDs and bitfield storage) are copied as is
+impl<const N: usize> WireCodec for [u8; N] {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        ensure_remaining(buf, N)?;
+        let mut array = [0; N];
+        buf.copy_to_slice(&mut array);
+        Ok(array)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_slice(self);
+    }
+}
+
+/// Returns an error when `buf` holds less than `size` bytes.
+pub fn ensure_remaining(buf: &Bytes, size: usize) -> MctpBaseLibResult<()> {
+    if buf.len() < size {
+        return Err(MctpBaseLibError::InvalidPayloadSize {
+            expected: size.to_string(),
+            found: buf.len().to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Decodes a fixed size (`#[repr(C, packed)]`) structure from the front of `buf`.
+pub fn decode_fixed<T>(buf: &mut Bytes) -> MctpBaseLibResult<T>
+where
+    T: TryFrom<Bytes, Error = MctpBaseLibError>,
+{
+    let size = mem::size_of::<T>();
+    ensure_remaining(buf, size)?;
+    T::try_from(buf.split_to(size))
+}
+
+/// Decodes exactly `count` entries from the front of `buf`.
+pub fn decode_entries<T: WireCodec>(buf: &mut Bytes, count: usize) -> MctpBaseLibResult<Vec<T>> {
+    (0..count).map(|_| T::decode_from(buf)).collect()
+}
+
+/// Returns an error when a count prefixed list of `count` entries (or bytes) doesn't fit its count
+/// byte. Messages holding such a list check it when they are built.
+pub fn check_count(count: usize) -> MctpBaseLibResult<()> {
+    if count > u8::MAX as usize {
+        return Err(MctpBaseLibError::InvalidPayloadSize {
+            expected: format!("at most {} entries", u8::MAX),
+            found: format!("{} entries", count),
+        });
+    }
+    Ok(())
+}
+
+/// Appends the count byte of a count prefixed list of `count` entries (or bytes) and returns the
+/// number of entries to encode after it. A list that skipped [`check_count`] is cut to the first
+/// 255 entries so the count always matches what follows it.
+pub fn encode_count(buf: &mut BytesMut, count: usize) -> usize {
+    let count = count.min(u8::MAX as usize);
+    buf.put_u8(count as u8);
+    count
+}
+
+/// Appends every entry to `buf` in order.
+pub fn encode_entries<T: WireCodec>(buf: &mut BytesMut, entries: &[T]) {
+    for entry in entries {
+        entry.encode_into(buf);
+    }
+}
@@ -1,10 +1,29 @@
 //! Defines MCTP Control Protocol layer
 pub mod allocate_eids;
+pub mod discovery_notify;
+pub mod endpoint_discovery;
 pub mod enums;
 pub mod get_eid;
+pub mod get_message_type_support;
+pub mod get_network_id;
 pub mod get_routing_table;
+pub mod get_uuid;
+pub mod get_vendor_message_support;
+pub mod get_version_support;
+pub mod message;
 pub mod models;
+pub mod paging;
+pub mod prepare_endpoint_discovery;
+pub mod query_hop;
+pub mod query_rate_limit;
+pub mod query_supported_interfaces;
+pub mod request_tx_rate_limit;
+pub mod resolve_eid;
+pub mod resolve_uuid;
+pub mod routing_info_update;
 pub mod set_eid;
+pub mod update_rate_limit;
+pub mod validation;
 
 use anyhow::{Context, Result};
 use bytes::{BufMut, Bytes, BytesMut};
@@ -19,6 +38,9 @@ use crate::{
 pub use self::models::EmptyRequest;
 pub use self::models::EmptyResponse;
 
+pub use self::message::{ControlCommand, ControlMessage, ControlRequest, ControlResponse};
+pub use self::validation::{MessageDirection, ProtocolViolation, StrictValidator};
+
 trait ControlMsgBody {}
 
 struct NullControlMsg {}
@@ -26,8 +48,15 @@ struct NullControlMsg {}
 impl ControlMsgBody for NullControlMsg {}
 
 pub trait ControlMsgReponseStatus {
+    /// The completion code, with command specific and reserved codes mapped to
+    /// `ErrorUnsupportedCmd`
+    #[deprecated(note = "loses command specific codes, use `response_code` instead")]
     fn completion_code(&self) -> CompletionCode;
-    fn is_success(&self) -> Result<()>;
+    /// The completion code as received, including command specific codes
+    fn response_code(&self) -> ResponseCode;
+    /// Returns an `ErrorResponse` carrying the command and completion code unless the response
+    /// reports success.
+    fn is_success(&self) -> Result<(), MctpBaseLibError>;
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Default)]
@@ -52,14 +81,14 @@ impl ControlPayload {
         }
     }
 
-    pub fn try_to_response<T: TryFrom<Bytes>>(bytes: Bytes) -> Result<(ControlPayload, T)> {
-        let payload = Self::try_from(bytes).context("Failed parsing payload")?;
-        match T::try_from(payload.payload.clone()) {
-            Ok(resp) => Ok((payload, resp)),
-            // TODO: find a way to pull out the error instead of just propogating a new generic message
-            Err(_) => Err(anyhow::Error::msg("Failed parsing response")),
-        }
-        // TODO: expose "completion_code" to commonize success check here
+    /// Decodes a message and the response it carries. Decode failures are returned as is.
+    pub fn try_to_response<T>(bytes: Bytes) -> Result<(ControlPayload, T), MctpBaseLibError>
+    where
+        T: TryFrom<Bytes, Error = MctpBaseLibError>,
+    {
+        let payload = Self::try_from(bytes)?;
+        let resp = T::try_from(payload.payload.clone())?;
+        Ok((payload, resp))
     }
 
     pub fn command_code(&self) -> Result<CommandCode, MctpBaseLibError> {
@@ -79,6 +108,32 @@ impl ControlPayload {
         let rsp_hdr = self.hdr.create_response();
         ControlPayload::new(rsp_hdr, control_hdr, response_body)
     }
+
+    /// Creates a response to the request in this payload that only carries `completion_code`, e.g.
+    /// to reject a request that couldn't be decoded.
+    pub fn create_error_response(&self, completion_code: CompletionCode) -> ControlPayload {
+        let mut control_hdr = self.control_hdr;
+        control_hdr.set_rq(0);
+        control_hdr.set_d_bit(0);
+        control_hdr.set_integrity_check(0);
+        control_hdr.set_rsvd(0);
+        let resp = EmptyResponse::new(control_hdr, completion_code as u8);
+        self.create_response_payload(control_hdr, resp.into())
+    }
+
+    /// Creates the response rejecting the request in this payload with the completion code
+    /// matching `err`. Messages that aren't requests can't be answered, so there is none for them.
+    pub fn create_rejection(&self, err: &MctpBaseLibError) -> Option<ControlPayload> {
+        if self.hdr.tag_owner() == 0 {
+            return None;
+        }
+        Some(self.create_error_response(err.completion_code()))
+    }
+
+    /// Creates the response to a request for a command the endpoint doesn't support.
+    pub fn create_unsupported_response(&self) -> ControlPayload {
+        self.create_error_response(CompletionCode::ErrorUnsupportedCmd)
+    }
 }
 
 impl From<ControlPayload> for Vec<u8> {
@@ -140,14 +195,16 @@ mod tests {
     use anyhow::Result;
 
     #[test]
-    fn test_completion_code_serialize() -> Result<()> {
-        let bytes: Vec<u8> = vec![
+    fn test_control_payload_try_from_bytes() -> Result<()> {
+        let bytes = Bytes::from(vec![
             0x01, 0x02, 0x0a, 0xc0, 0x00, 0x00, 0x02, 0x00, 0x0a, 0x10, 0x00, 0x9c,
-        ];
+        ]);
 
-        let ctrl_payload = ControlPayload::try_from(bytes).unwrap()?;
+        let ctrl_payload = ControlPayload::try_from(bytes)?;
+        assert_eq!(ctrl_payload.command_code()?, CommandCode::GetEndpointID);
 
-        assert_eq!(bincode::serialize(&completion_code)?, bytes);
+        let resp = get_eid::Response::try_from(ctrl_payload)?;
+        assert_eq!(resp.eid, 0x0a);
 
         Ok(())
     }
@@ -165,4 +222,41 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_create_rejection() -> Result<()> {
+        // GetEndpointID request with the D bit set
+        let bytes = Bytes::from(vec![0x01, 0x0a, 0x08, 0xc8, 0x00, 0xc0, 0x02]);
+        let payload = ControlPayload::try_from(bytes)?;
+        let err = MctpBaseLibError::InvalidPayloadSize {
+            expected: "0".into(),
+            found: "1".into(),
+        };
+
+        let resp = payload.create_rejection(&err).unwrap();
+        assert_eq!(resp.hdr.destination_eid, 0x08);
+        assert_eq!(resp.hdr.tag_owner(), 0);
+        assert_eq!(resp.control_hdr.rq(), 0);
+        assert_eq!(resp.control_hdr.d_bit(), 0);
+        assert_eq!(
+            &resp.payload[..],
+            &[0x00, 0x00, 0x02, CompletionCode::ErrorInvalidLength as u8]
+        );
+
+        // responses can't be answered
+        let resp = ControlPayload::try_from(Bytes::from(resp))?;
+        assert_eq!(resp.create_rejection(&err), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_to_response_keeps_decode_error() {
+        // GetEndpointID response cut short after the completion code
+        let bytes = Bytes::from(vec![0x01, 0x08, 0x0a, 0xc0, 0x00, 0x00, 0x02, 0x00]);
+        assert!(matches!(
+            ControlPayload::try_to_response::<get_eid::Response>(bytes),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+    }
 }
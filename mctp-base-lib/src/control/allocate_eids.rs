@@ -25,12 +25,25 @@ pub enum Operation {
     GetAllocationInfo = 2,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Default,
+    c2rust_bitfields::BitfieldStruct,
+    mctp_emu_derive::BitfieldView,
+)]
 #[mctp_emu_derive::add_from_control_payload_derives]
 #[repr(C, packed)]
 pub struct Request {
     pub hdr: ControlMsgHeader,
-    #[bitfield(name = "operation", ty = "uint8_t", bits = "0..=1")]
+    #[bitfield(
+        name = "raw_operation",
+        enum_ty = "Operation",
+        ty = "uint8_t",
+        bits = "0..=1"
+    )]
     #[bitfield(name = "reserved", ty = "uint8_t", bits = "2..=7")]
     operation_reserved: [u8; 1],
     pub number_of_eids: uint8_t,
@@ -51,7 +64,7 @@ impl Request {
                 number_of_eids,
                 starting_eid,
             };
-            ..set_operation(operation as uint8_t);
+            ..set_operation(operation);
         }
     }
 }
@@ -77,11 +90,11 @@ pub enum AllocationStatus {
 #[derive(
     Copy,
     Clone,
-    Debug,
     PartialEq,
     Eq,
     Default,
     c2rust_bitfields::BitfieldStruct,
+    mctp_emu_derive::BitfieldView,
     mctp_emu_derive::AddControlMsgResponse,
 )]
 #[mctp_emu_derive::add_from_control_payload_derives]
@@ -89,7 +102,12 @@ pub enum AllocationStatus {
 pub struct Response {
     pub hdr: ControlMsgHeader,
     pub completion_code: uint8_t,
-    #[bitfield(name = "raw_allocation_status", ty = "uint8_t", bits = "0..=1")]
+    #[bitfield(
+        name = "raw_allocation_status",
+        enum_ty = "AllocationStatus",
+        ty = "uint8_t",
+        bits = "0..=1"
+    )]
     #[bitfield(name = "reserved", ty = "uint8_t", bits = "2..=7")]
     allocation_status_reserved: [u8; 1],
     pub eid_pool_size: uint8_t,
@@ -112,7 +130,7 @@ impl Response {
                 first_eid,
                 eid_pool_size,
             };
-            ..set_raw_allocation_status(allocation_status as u8);
+            ..set_allocation_status(allocation_status);
         }
     }
 
@@ -133,8 +151,4 @@ impl Response {
             first_eid,
         )
     }
-
-    pub fn allocation_status(&self) -> AllocationStatus {
-        AllocationStatus::from(self.raw_allocation_status())
-    }
 }
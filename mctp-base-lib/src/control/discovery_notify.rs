@@ -0,0 +1,7 @@
+//! Discovery Notify: sent by an endpoint to the bus owner once it becomes available on a bus that
+//! has no other way of announcing new devices.
+use crate::control::models::{EmptyRequest, EmptyResponse};
+
+// Has no additional fields
+pub type Request = EmptyRequest;
+pub type Response = EmptyResponse;
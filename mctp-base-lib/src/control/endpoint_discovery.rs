@@ -0,0 +1,7 @@
+//! Endpoint Discovery: broadcast by the bus owner, only answered by endpoints whose "discovered"
+//! flag is still clear.
+use crate::control::models::{EmptyRequest, EmptyResponse};
+
+// Has no additional fields
+pub type Request = EmptyRequest;
+pub type Response = EmptyResponse;
@@ -1,7 +1,10 @@
+use bytes::{Bytes, BytesMut};
 use mctp_emu_derive::{DeserializeU8Enum, SerializeU8Enum};
 use num_enum::{FromPrimitive};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::base::{MctpBaseLibResult, WireCodec};
+
 /// A list of supported Command Codes
 #[derive(
     Debug, PartialEq, Copy, Clone, DeserializeU8Enum, SerializeU8Enum, Default, Ord, PartialOrd, Eq, FromPrimitive,
@@ -68,6 +71,16 @@ pub enum CommandCode {
     Unknown = 0xFF,
 }
 
+impl WireCodec for CommandCode {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        Ok(Self::from(u8::decode_from(buf)?))
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        (*self as u8).encode_into(buf);
+    }
+}
+
 /// This field is only present in Response messages. This field contains a
 /// value that indicates whether the response completed normally. If the
 /// command did not complete normally, the value can provide additional
@@ -100,6 +113,66 @@ pub enum CompletionCode {
     ErrorUnsupportedCmd = 0x05,
 }
 
+/// A completion code as received in a response. Unlike [`CompletionCode`], it keeps the command
+/// specific codes (`0x80` to `0xFF`) and the reserved ones instead of mapping them to
+/// `ErrorUnsupportedCmd`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResponseCode {
+    /// One of the codes shared by every command
+    Generic(CompletionCode),
+    /// A code whose meaning is defined by the command, e.g. `MESSAGE_TYPE_NOT_SUPPORTED`
+    CommandSpecific(u8),
+    /// A code in the range reserved by DSP0236
+    Reserved(u8),
+}
+
+impl ResponseCode {
+    pub fn is_success(&self) -> bool {
+        *self == Self::Generic(CompletionCode::Success)
+    }
+}
+
+impl From<u8> for ResponseCode {
+    fn from(code: u8) -> Self {
+        match CompletionCode::from(code) {
+            generic if generic as u8 == code => Self::Generic(generic),
+            _ if code >= 0x80 => Self::CommandSpecific(code),
+            _ => Self::Reserved(code),
+        }
+    }
+}
+
+impl From<CompletionCode> for ResponseCode {
+    fn from(code: CompletionCode) -> Self {
+        Self::Generic(code)
+    }
+}
+
+impl PartialEq<CompletionCode> for ResponseCode {
+    fn eq(&self, other: &CompletionCode) -> bool {
+        *self == Self::Generic(*other)
+    }
+}
+
+impl From<ResponseCode> for u8 {
+    fn from(code: ResponseCode) -> Self {
+        match code {
+            ResponseCode::Generic(code) => code as u8,
+            ResponseCode::CommandSpecific(code) | ResponseCode::Reserved(code) => code,
+        }
+    }
+}
+
+impl std::fmt::Display for ResponseCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Generic(code) => write!(f, "{:?}", code),
+            Self::CommandSpecific(code) => write!(f, "command specific code {:#04x}", code),
+            Self::Reserved(code) => write!(f, "reserved code {:#04x}", code),
+        }
+    }
+}
+
 /// The Message Type of the MCTP packet
 #[derive(
     Debug, PartialEq, Eq, Copy, Clone, DeserializeU8Enum, SerializeU8Enum, FromPrimitive, Default,
@@ -187,6 +260,24 @@ pub enum PhysicalTransportBinding {
     VendorDefined = 0xff,
 }
 
+/// Describes what a routing entry's EID range corresponds to. Shared by the Routing Information
+/// Update and Get Routing Table Entries commands.
+#[derive(
+    Debug, PartialEq, Eq, Copy, Clone, DeserializeU8Enum, SerializeU8Enum, FromPrimitive, Default,
+)]
+#[repr(u8)]
+pub enum RoutingEntryType {
+    /// A single endpoint that does not serve as an MCTP bridge
+    #[default]
+    SingleEndpoint = 0,
+    /// An EID range for a bridge, starting with the EID of the bridge itself
+    BridgeAndDownstreamEids = 1,
+    /// A single endpoint that serves as an MCTP bridge
+    SingleBridge = 2,
+    /// An EID range for a bridge that does not include the EID of the bridge itself
+    AdditionalBridgeEids = 3,
+}
+
 /// Add a few tests to ensure conversion to/from various types: Bytes, Vec, u8/uint8_t
 #[cfg(test)]
 mod tests {
@@ -195,7 +286,7 @@ mod tests {
 
     #[test]
     fn test_completion_code_serialize() -> Result<()> {
-        let bytes = vec![0x03 as u8];
+        let bytes = vec![0x03u8];
 
         let completion_code = CompletionCode::ErrorInvalidLength;
 
@@ -235,4 +326,19 @@ mod tests {
         assert_eq!(CompletionCode::ErrorUnsupportedCmd, CompletionCode::from(5));
         assert_eq!(CompletionCode::ErrorUnsupportedCmd, CompletionCode::from(0x55));
     }
+
+    #[test]
+    fn test_response_code_keeps_command_specific_codes() {
+        assert_eq!(
+            ResponseCode::from(0x02),
+            ResponseCode::Generic(CompletionCode::ErrorInvalidData)
+        );
+        assert_eq!(
+            ResponseCode::from(0x80),
+            ResponseCode::CommandSpecific(0x80)
+        );
+        assert_eq!(ResponseCode::from(0x42), ResponseCode::Reserved(0x42));
+        assert_eq!(u8::from(ResponseCode::from(0x80)), 0x80);
+        assert!(ResponseCode::from(0x00).is_success());
+    }
 }
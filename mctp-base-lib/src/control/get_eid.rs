@@ -4,7 +4,7 @@ use cascade::cascade;
 use crate::{
     base::*,
     // control::ControlMsgReponseStatus,
-    control::{models::ControlMsgHeader, CompletionCode, ControlPayload},
+    control::{models::ControlMsgHeader, CompletionCode},
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
@@ -55,11 +55,11 @@ pub enum EndpointType {
 #[derive(
     Copy,
     Clone,
-    Debug,
     PartialEq,
     Eq,
     Default,
     c2rust_bitfields::BitfieldStruct,
+    mctp_emu_derive::BitfieldView,
     mctp_emu_derive::AddControlMsgResponse,
 )]
 #[mctp_emu_derive::add_from_control_payload_derives]
@@ -68,9 +68,19 @@ pub struct Response {
     pub hdr: ControlMsgHeader,
     pub completion_code: uint8_t,
     pub eid: uint8_t,
-    #[bitfield(name = "eid_type", ty = "uint8_t", bits = "0..=1")]
+    #[bitfield(
+        name = "raw_eid_type",
+        enum_ty = "EidType",
+        ty = "uint8_t",
+        bits = "0..=1"
+    )]
     #[bitfield(name = "reserved1", ty = "uint8_t", bits = "2..=3")]
-    #[bitfield(name = "endpoint_type", ty = "uint8_t", bits = "4..=5")]
+    #[bitfield(
+        name = "raw_endpoint_type",
+        enum_ty = "EndpointType",
+        ty = "uint8_t",
+        bits = "4..=5"
+    )]
     #[bitfield(name = "reserved2", ty = "uint8_t", bits = "6..=7")]
     eid_endpoint_type: [u8; 1],
     pub medium_specific: uint8_t,
@@ -94,8 +104,8 @@ impl Response {
                 eid_endpoint_type: [0; 1],
                 medium_specific,
             };
-            ..set_eid_type(eid_type as u8);
-            ..set_endpoint_type(endpoint_type as u8);
+            ..set_eid_type(eid_type);
+            ..set_endpoint_type(endpoint_type);
         }
     }
 
@@ -107,7 +117,7 @@ impl Response {
         endpoint_type: EndpointType,
         medium_specific: uint8_t,
     ) -> Self {
-        let mut hdr = req.hdr.clone();
+        let mut hdr = req.hdr;
         hdr.set_rq(0);
         Self::new(
             hdr,
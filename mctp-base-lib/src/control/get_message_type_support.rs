@@ -0,0 +1,128 @@
+use anyhow::Error;
+
+use crate::{
+    base::*,
+    control::{models::ControlMsgHeader, CompletionCode},
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+}
+
+/// Lists the message types supported in addition to the MCTP control message type. The list is
+/// only present when the completion code is `Success`.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    mctp_emu_derive::AddControlMsgResponse,
+    mctp_emu_derive::WireMessage,
+)]
+#[wire(control_payload)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    #[wire(on_success, count_prefixed)]
+    pub message_types: Vec<uint8_t>,
+}
+
+impl Response {
+    pub fn new(
+        hdr: ControlMsgHeader,
+        completion_code: uint8_t,
+        message_types: Vec<uint8_t>,
+    ) -> MctpBaseLibResult<Self> {
+        check_count(message_types.len())?;
+        Ok(Self {
+            hdr,
+            completion_code,
+            message_types,
+        })
+    }
+
+    pub fn from(
+        req: Request,
+        completion_code: CompletionCode,
+        message_types: Vec<uint8_t>,
+    ) -> MctpBaseLibResult<Self> {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(hdr, completion_code as uint8_t, message_types)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::enums::{CommandCode, MessageType};
+    use anyhow::Result;
+
+    #[test]
+    fn test_response_round_trip() -> Result<()> {
+        let hdr = ControlMsgHeader::new(CommandCode::GetMessageTypeSupport, 0, false, true, false);
+        let message_types = vec![MessageType::Pldm as u8, MessageType::VendorDefinedPCI as u8];
+        let resp = Response::from(
+            Request { hdr },
+            CompletionCode::Success,
+            message_types.clone(),
+        )?;
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(&bytes[3..], &[0x00, 0x02, 0x01, 0x7E]);
+
+        let decoded = Response::try_from(bytes)?;
+        assert_eq!(decoded.message_types, message_types);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_list_too_long() -> Result<()> {
+        let hdr = ControlMsgHeader::new(CommandCode::GetMessageTypeSupport, 0, false, true, false);
+        let resp = Response::from(Request { hdr }, CompletionCode::Success, vec![0x01; 255])?;
+        assert_eq!(Bytes::from(resp)[4], 0xFF);
+
+        assert!(matches!(
+            Response::from(Request { hdr }, CompletionCode::Success, vec![0x01; 256]),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_count_mismatch() {
+        // count says 3 entries, only 2 follow
+        let short = Bytes::from(vec![0x00, 0x00, 0x04, 0x00, 0x03, 0x01, 0x7E]);
+        assert!(matches!(
+            Response::try_from(short),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+
+        // count says 1 entry, 2 follow
+        let long = Bytes::from(vec![0x00, 0x00, 0x04, 0x00, 0x01, 0x01, 0x7E]);
+        assert!(matches!(
+            Response::try_from(long),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+    }
+
+    #[test]
+    fn test_error_response_has_no_list() -> Result<()> {
+        let hdr = ControlMsgHeader::new(CommandCode::GetMessageTypeSupport, 0, false, true, false);
+        let resp = Response::from(Request { hdr }, CompletionCode::Error, vec![0x01])?;
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(&bytes[3..], &[CompletionCode::Error as u8]);
+
+        let decoded = Response::try_from(bytes)?;
+        assert!(decoded.message_types.is_empty());
+
+        Ok(())
+    }
+}
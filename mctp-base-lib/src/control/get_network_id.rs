@@ -0,0 +1,80 @@
+use anyhow::Error;
+use uuid::Uuid;
+
+use crate::{
+    base::*,
+    control::{models::ControlMsgHeader, CompletionCode},
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    c2rust_bitfields::BitfieldStruct,
+    mctp_emu_derive::AddControlMsgResponse,
+)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    /// Network ID (a UUID) in RFC4122 (network) byte order
+    raw_network_id: [u8; 16],
+}
+
+impl Response {
+    pub fn new(hdr: ControlMsgHeader, completion_code: uint8_t, network_id: Uuid) -> Self {
+        Self {
+            hdr,
+            completion_code,
+            raw_network_id: *network_id.as_bytes(),
+        }
+    }
+
+    pub fn from(req: Request, completion_code: CompletionCode, network_id: Uuid) -> Self {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(hdr, completion_code as uint8_t, network_id)
+    }
+
+    pub fn network_id(&self) -> Uuid {
+        Uuid::from_bytes(self.raw_network_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::enums::CommandCode;
+    use anyhow::Result;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_response_round_trip() -> Result<()> {
+        let network_id = Uuid::from_bytes([
+            0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab,
+            0xcd, 0xef,
+        ]);
+        let hdr = ControlMsgHeader::new(CommandCode::GetNetworkID, 0, false, true, false);
+        let resp = Response::from(Request { hdr }, CompletionCode::Success, network_id);
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(bytes.len(), 20);
+        assert_eq!(&bytes[4..], network_id.as_bytes());
+
+        let decoded = Response::try_from(bytes)?;
+        assert_eq!(decoded.network_id(), network_id);
+
+        Ok(())
+    }
+}
@@ -1,17 +1,23 @@
 use anyhow::Error;
-use cascade::cascade;
+use bytes::{BufMut, Bytes, BytesMut};
 
 use crate::{
     base::*,
-    control::{enums::*, models::*, *},
+    control::{enums::*, models::*, paging::page_entries, *},
 };
 
+pub use crate::control::paging::NO_MORE_ENTRIES;
+
+/// Largest port number a routing table entry can hold
+pub const MAX_PORT_NUMBER: uint8_t = 0x1f;
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
 #[mctp_emu_derive::add_from_control_payload_derives]
 #[repr(C, packed)]
 pub struct Request {
     pub hdr: ControlMsgHeader,
-    entry_handle: uint8_t,
+    /// Handle of the first entry to return, `0x00` for the start of the table
+    pub entry_handle: uint8_t,
 }
 
 impl Request {
@@ -20,57 +26,256 @@ impl Request {
     }
 }
 
+/// A single routing table entry. The physical address is medium specific and is prefixed on the
+/// wire by its size.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct RoutingTableEntry {
+    pub eid_range_size: uint8_t,
+    pub starting_eid: uint8_t,
+    pub entry_type: RoutingEntryType,
+    /// Entry was configured statically rather than through discovery or Routing Information Update
+    pub static_entry: bool,
+    /// Bridge port the EID range is reached through
+    pub port_number: uint8_t,
+    pub binding_type: PhysicalTransportBinding,
+    pub medium_type: PhysicalMediumIdentifier,
+    pub physical_address: Bytes,
+}
+
+impl RoutingTableEntry {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<T: Into<Bytes>>(
+        eid_range_size: uint8_t,
+        starting_eid: uint8_t,
+        entry_type: RoutingEntryType,
+        static_entry: bool,
+        port_number: uint8_t,
+        binding_type: PhysicalTransportBinding,
+        medium_type: PhysicalMediumIdentifier,
+        physical_address: T,
+    ) -> MctpBaseLibResult<Self> {
+        let physical_address = physical_address.into();
+        check_count(physical_address.len())?;
+        Ok(Self {
+            eid_range_size,
+            starting_eid,
+            entry_type,
+            static_entry,
+            port_number,
+            binding_type,
+            medium_type,
+            physical_address,
+        })
+    }
+
+    /// Number of bytes the entry takes up in a response
+    pub fn encoded_len(&self) -> usize {
+        6 + self.physical_address.len()
+    }
+}
+
+impl WireCodec for RoutingTableEntry {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        let eid_range_size = u8::decode_from(buf)?;
+        let starting_eid = u8::decode_from(buf)?;
+        let entry_info = u8::decode_from(buf)?;
+        let binding_type = PhysicalTransportBinding::from(u8::decode_from(buf)?);
+        let medium_type = PhysicalMediumIdentifier::from(u8::decode_from(buf)?);
+        let address_size = u8::decode_from(buf)? as usize;
+        ensure_remaining(buf, address_size)?;
+        Self::new(
+            eid_range_size,
+            starting_eid,
+            RoutingEntryType::from(entry_info >> 6),
+            entry_info & 0x20 != 0,
+            entry_info & MAX_PORT_NUMBER,
+            binding_type,
+            medium_type,
+            buf.split_to(address_size),
+        )
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u8(self.eid_range_size);
+        buf.put_u8(self.starting_eid);
+        buf.put_u8(
+            (self.entry_type as uint8_t) << 6
+                | (self.static_entry as uint8_t) << 5
+                | (self.port_number & MAX_PORT_NUMBER),
+        );
+        buf.put_u8(self.binding_type as uint8_t);
+        buf.put_u8(self.medium_type as uint8_t);
+        let count = encode_count(buf, self.physical_address.len());
+        buf.put_slice(&self.physical_address[..count]);
+    }
+}
+
+/// The next entry handle and entries are only present when the completion code is `Success`.
 #[derive(
-    Copy,
     Clone,
     Debug,
     PartialEq,
     Eq,
     Default,
-    c2rust_bitfields::BitfieldStruct,
     mctp_emu_derive::AddControlMsgResponse,
+    mctp_emu_derive::WireMessage,
 )]
-#[mctp_emu_derive::add_from_control_payload_derives]
-#[repr(C, packed)]
+#[wire(control_payload)]
 pub struct Response {
     pub hdr: ControlMsgHeader,
     pub completion_code: uint8_t,
+    /// Handle to use for the next request or [`NO_MORE_ENTRIES`]
+    #[wire(on_success)]
     pub next_entry_handle: uint8_t,
-    pub entries_in_response: uint8_t,
+    #[wire(on_success, count_prefixed)]
+    pub entries: Vec<RoutingTableEntry>,
 }
 
-// TODO: support a single entry to start with instead of trying to do dynamic sizing
-// Can use a Byte array to support any number of entries with methods to add entries to the list
-// Or can just take a static size within "from|new".
-
 impl Response {
     pub fn new(
         hdr: ControlMsgHeader,
         completion_code: uint8_t,
         next_entry_handle: uint8_t,
-        entries_in_response: uint8_t,
-    ) -> Self {
-        Self {
+        entries: Vec<RoutingTableEntry>,
+    ) -> MctpBaseLibResult<Self> {
+        check_count(entries.len())?;
+        Ok(Self {
             hdr,
             completion_code,
             next_entry_handle,
-            entries_in_response,
-        }
+            entries,
+        })
     }
 
     pub fn from(
         req: Request,
         completion_code: CompletionCode,
         next_entry_handle: uint8_t,
-        entries_in_response: uint8_t,
-    ) -> Self {
+        entries: Vec<RoutingTableEntry>,
+    ) -> MctpBaseLibResult<Self> {
         let mut hdr = req.hdr;
         hdr.set_rq(0);
-        Self::new(
-            hdr,
-            completion_code as uint8_t,
-            next_entry_handle,
-            entries_in_response,
+        Self::new(hdr, completion_code as uint8_t, next_entry_handle, entries)
+    }
+
+    /// Builds the response for the page of `table` starting at the requested entry handle, fitting
+    /// as many entries as possible into a message of `max_msg_size` bytes. The entry handle is the
+    /// index of the entry in `table`.
+    pub fn from_table(req: Request, table: &[RoutingTableEntry], max_msg_size: usize) -> Self {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        // a page never holds more entries than its count byte can describe
+        match page_entries(
+            table,
+            req.entry_handle,
+            max_msg_size,
+            RoutingTableEntry::encoded_len,
+        ) {
+            Some((next_entry_handle, entries)) => Self {
+                hdr,
+                completion_code: CompletionCode::Success as uint8_t,
+                next_entry_handle,
+                entries,
+            },
+            None => Self {
+                hdr,
+                completion_code: CompletionCode::ErrorInvalidData as uint8_t,
+                next_entry_handle: NO_MORE_ENTRIES,
+                entries: Vec::new(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    fn request(entry_handle: uint8_t) -> Request {
+        let hdr = ControlMsgHeader::new(CommandCode::GetRoutingTableEntries, 0, false, true, false);
+        Request::new(hdr, entry_handle)
+    }
+
+    fn entry(starting_eid: uint8_t) -> RoutingTableEntry {
+        RoutingTableEntry::new(
+            1,
+            starting_eid,
+            RoutingEntryType::SingleEndpoint,
+            false,
+            0,
+            PhysicalTransportBinding::MCTPoverSMBus,
+            PhysicalMediumIdentifier::SMBUS_2_0_100khz,
+            vec![starting_eid],
         )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_response_round_trip() -> Result<()> {
+        let table = vec![RoutingTableEntry::new(
+            8,
+            0x20,
+            RoutingEntryType::AdditionalBridgeEids,
+            true,
+            2,
+            PhysicalTransportBinding::MCTPoverSMBus,
+            PhysicalMediumIdentifier::SMBUS_2_0_100khz,
+            vec![0x30],
+        )?];
+        let resp = Response::from(request(0), CompletionCode::Success, NO_MORE_ENTRIES, table)?;
+
+        let bytes = Bytes::from(resp.clone());
+        assert_eq!(
+            &bytes[3..],
+            &[0x00, 0xFF, 0x01, 0x08, 0x20, 0xE2, 0x01, 0x01, 0x01, 0x30]
+        );
+        assert_eq!(Response::try_from(bytes)?, resp);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_from_table_pages() -> Result<()> {
+        let table: Vec<_> = (0x10..0x20).map(entry).collect();
+
+        // Each entry takes 7 bytes, so 4 of them fit after the 6 byte overhead
+        let resp = Response::from_table(request(0), &table, 34);
+        assert_eq!(resp.entries, table[..4]);
+        assert_eq!(resp.next_entry_handle, 4);
+
+        let resp = Response::from_table(request(12), &table, 34);
+        assert_eq!(resp.entries, table[12..]);
+        assert_eq!(resp.next_entry_handle, NO_MORE_ENTRIES);
+
+        let resp = Response::from_table(request(16), &table, 34);
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidData);
+
+        let resp = Response::from_table(request(0), &[], 34);
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        assert!(resp.entries.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_address_too_long() {
+        let entry = |address_size| {
+            RoutingTableEntry::new(
+                1,
+                0x20,
+                RoutingEntryType::SingleEndpoint,
+                false,
+                0,
+                PhysicalTransportBinding::MCTPoverSMBus,
+                PhysicalMediumIdentifier::SMBUS_2_0_100khz,
+                vec![0x30; address_size],
+            )
+        };
+        assert!(entry(255).is_ok());
+        assert!(matches!(
+            entry(256),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
     }
 }
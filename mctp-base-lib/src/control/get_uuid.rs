@@ -0,0 +1,81 @@
+use anyhow::Error;
+use uuid::Uuid;
+
+use crate::{
+    base::*,
+    control::{models::ControlMsgHeader, CompletionCode},
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+}
+
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    c2rust_bitfields::BitfieldStruct,
+    mctp_emu_derive::AddControlMsgResponse,
+)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    /// UUID in RFC4122 (network) byte order
+    raw_uuid: [u8; 16],
+}
+
+impl Response {
+    pub fn new(hdr: ControlMsgHeader, completion_code: uint8_t, uuid: Uuid) -> Self {
+        Self {
+            hdr,
+            completion_code,
+            raw_uuid: *uuid.as_bytes(),
+        }
+    }
+
+    pub fn from(req: Request, completion_code: CompletionCode, uuid: Uuid) -> Self {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(hdr, completion_code as uint8_t, uuid)
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        Uuid::from_bytes(self.raw_uuid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::enums::CommandCode;
+    use anyhow::Result;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_response_round_trip() -> Result<()> {
+        let uuid = Uuid::from_bytes([
+            0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab,
+            0xcd, 0xef,
+        ]);
+        let hdr = ControlMsgHeader::new(CommandCode::GetEndpointUUID, 0, false, true, false);
+        let resp = Response::from(Request { hdr }, CompletionCode::Success, uuid);
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(bytes.len(), 20);
+        assert_eq!(&bytes[4..], uuid.as_bytes());
+
+        let decoded = Response::try_from(bytes)?;
+        assert_eq!(decoded.uuid(), uuid);
+        assert_eq!(decoded.hdr.rq(), 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+use anyhow::Error;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{
+    base::*,
+    control::{models::ControlMsgHeader, CompletionCode},
+};
+
+/// Vendor ID set selector value that indicates there are no more capability sets
+pub const NO_MORE_SETS: uint8_t = 0xFF;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+    pub vendor_id_set_selector: uint8_t,
+}
+
+impl Request {
+    pub fn new(hdr: ControlMsgHeader, vendor_id_set_selector: uint8_t) -> Self {
+        Self {
+            hdr,
+            vendor_id_set_selector,
+        }
+    }
+}
+
+/// Vendor identifier, prefixed on the wire by its format byte. Both formats are sent big-endian.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VendorId {
+    /// 16-bit PCI Vendor ID (format `0x00`)
+    Pci(u16),
+    /// 32-bit IANA Enterprise Number (format `0x01`)
+    Iana(u32),
+}
+
+impl VendorId {
+    pub const FORMAT_PCI: uint8_t = 0x00;
+    pub const FORMAT_IANA: uint8_t = 0x01;
+
+    pub fn format(&self) -> uint8_t {
+        match self {
+            VendorId::Pci(_) => Self::FORMAT_PCI,
+            VendorId::Iana(_) => Self::FORMAT_IANA,
+        }
+    }
+}
+
+impl Default for VendorId {
+    fn default() -> Self {
+        VendorId::Pci(0)
+    }
+}
+
+impl WireCodec for VendorId {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        match u8::decode_from(buf)? {
+            Self::FORMAT_PCI => {
+                ensure_remaining(buf, 2)?;
+                Ok(VendorId::Pci(buf.get_u16()))
+            }
+            Self::FORMAT_IANA => {
+                ensure_remaining(buf, 4)?;
+                Ok(VendorId::Iana(buf.get_u32()))
+            }
+            format => Err(MctpBaseLibError::UnknownValue {
+                value: format!("vendor ID format {:#04x}", format),
+            }),
+        }
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u8(self.format());
+        match self {
+            VendorId::Pci(id) => buf.put_u16(*id),
+            VendorId::Iana(id) => buf.put_u32(*id),
+        }
+    }
+}
+
+/// A single vendor capability set: the vendor ID followed by a 16-bit vendor defined value (e.g. a
+/// command set type or version).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct VendorCapabilitySet {
+    pub vendor_id: VendorId,
+    pub command_set: u16,
+}
+
+impl VendorCapabilitySet {
+    pub fn new(vendor_id: VendorId, command_set: u16) -> Self {
+        Self {
+            vendor_id,
+            command_set,
+        }
+    }
+}
+
+impl WireCodec for VendorCapabilitySet {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        let vendor_id = VendorId::decode_from(buf)?;
+        ensure_remaining(buf, 2)?;
+        Ok(Self::new(vendor_id, buf.get_u16()))
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        self.vendor_id.encode_into(buf);
+        buf.put_u16(self.command_set);
+    }
+}
+
+/// The selector and capability set are only present when the completion code is `Success`.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    mctp_emu_derive::AddControlMsgResponse,
+    mctp_emu_derive::WireMessage,
+)]
+#[wire(control_payload)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    /// Selector to use for the next request or [`NO_MORE_SETS`]
+    #[wire(on_success)]
+    pub vendor_id_set_selector: uint8_t,
+    #[wire(on_success)]
+    pub capability_set: VendorCapabilitySet,
+}
+
+impl Response {
+    pub fn new(
+        hdr: ControlMsgHeader,
+        completion_code: uint8_t,
+        vendor_id_set_selector: uint8_t,
+        capability_set: VendorCapabilitySet,
+    ) -> Self {
+        Self {
+            hdr,
+            completion_code,
+            vendor_id_set_selector,
+            capability_set,
+        }
+    }
+
+    pub fn from(
+        req: Request,
+        completion_code: CompletionCode,
+        vendor_id_set_selector: uint8_t,
+        capability_set: VendorCapabilitySet,
+    ) -> Self {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(
+            hdr,
+            completion_code as uint8_t,
+            vendor_id_set_selector,
+            capability_set,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::enums::CommandCode;
+    use anyhow::Result;
+
+    fn request() -> Request {
+        let hdr = ControlMsgHeader::new(
+            CommandCode::GetVendorDefinedMessageSupport,
+            0,
+            false,
+            true,
+            false,
+        );
+        Request::new(hdr, 0)
+    }
+
+    #[test]
+    fn test_pci_response_round_trip() -> Result<()> {
+        let set = VendorCapabilitySet::new(VendorId::Pci(0x10de), 0x0102);
+        let resp = Response::from(request(), CompletionCode::Success, 1, set);
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(&bytes[3..], &[0x00, 0x01, 0x00, 0x10, 0xde, 0x01, 0x02]);
+        assert_eq!(Response::try_from(bytes)?.capability_set, set);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_iana_response_round_trip() -> Result<()> {
+        let set = VendorCapabilitySet::new(VendorId::Iana(0x0000_1234), 0x0001);
+        let resp = Response::from(request(), CompletionCode::Success, NO_MORE_SETS, set);
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(
+            &bytes[3..],
+            &[0x00, 0xFF, 0x01, 0x00, 0x00, 0x12, 0x34, 0x00, 0x01]
+        );
+
+        let decoded = Response::try_from(bytes)?;
+        assert_eq!(decoded.vendor_id_set_selector, NO_MORE_SETS);
+        assert_eq!(decoded.capability_set, set);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_vendor_id_format() {
+        let bytes = Bytes::from(vec![0x00, 0x00, 0x06, 0x00, 0xFF, 0x02, 0x00, 0x00]);
+        assert!(matches!(
+            Response::try_from(bytes),
+            Err(MctpBaseLibError::UnknownValue { .. })
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+use anyhow::Error;
+
+use crate::{
+    base::*,
+    control::{models::ControlMsgHeader, CompletionCode},
+};
+
+/// Message type number used to query the version of the MCTP base specification
+pub const MCTP_BASE_SPEC_MSG_TYPE: uint8_t = 0xFF;
+
+/// Command specific completion code returned when the requested message type is not supported
+pub const MESSAGE_TYPE_NOT_SUPPORTED: uint8_t = 0x80;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+    pub message_type_number: uint8_t,
+}
+
+impl Request {
+    pub fn new(hdr: ControlMsgHeader, message_type_number: uint8_t) -> Self {
+        Self {
+            hdr,
+            message_type_number,
+        }
+    }
+}
+
+/// A single MCTP version number entry. Each of the version fields is BCD encoded with `0xF` in the
+/// upper nibble for values below 10 (e.g. 1.3.1 is encoded as `F1 F3 F1 00`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_binary_derives]
+#[repr(C, packed)]
+pub struct VersionEntry {
+    pub major: uint8_t,
+    pub minor: uint8_t,
+    pub update: uint8_t,
+    pub alpha: uint8_t,
+}
+
+/// Version 1.3.1 of the MCTP base specification
+pub const MCTP_BASE_SPEC_VERSION: VersionEntry = VersionEntry::new(0xF1, 0xF3, 0xF1, 0x00);
+
+impl VersionEntry {
+    pub const fn new(major: uint8_t, minor: uint8_t, update: uint8_t, alpha: uint8_t) -> Self {
+        Self {
+            major,
+            minor,
+            update,
+            alpha,
+        }
+    }
+
+    /// Builds an entry from plain version numbers (no alpha), e.g. `from_version(1, 3, 1)`. Each
+    /// number has to fit in two BCD digits.
+    pub fn from_version(
+        major: uint8_t,
+        minor: uint8_t,
+        update: uint8_t,
+    ) -> MctpBaseLibResult<Self> {
+        Ok(Self::new(
+            Self::encode_bcd(major)?,
+            Self::encode_bcd(minor)?,
+            Self::encode_bcd(update)?,
+            0,
+        ))
+    }
+
+    fn encode_bcd(value: uint8_t) -> MctpBaseLibResult<uint8_t> {
+        match value {
+            0..=9 => Ok(0xF0 | value),
+            10..=99 => Ok(((value / 10) << 4) | (value % 10)),
+            _ => Err(MctpBaseLibError::UnknownValue {
+                value: format!("version number {}", value),
+            }),
+        }
+    }
+}
+
+/// The version entries are only present when the completion code is `Success`.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    mctp_emu_derive::AddControlMsgResponse,
+    mctp_emu_derive::WireMessage,
+)]
+#[wire(control_payload)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    #[wire(on_success, count_prefixed)]
+    pub entries: Vec<VersionEntry>,
+}
+
+impl Response {
+    pub fn new(
+        hdr: ControlMsgHeader,
+        completion_code: uint8_t,
+        entries: Vec<VersionEntry>,
+    ) -> MctpBaseLibResult<Self> {
+        check_count(entries.len())?;
+        Ok(Self {
+            hdr,
+            completion_code,
+            entries,
+        })
+    }
+
+    pub fn from(
+        req: Request,
+        completion_code: CompletionCode,
+        entries: Vec<VersionEntry>,
+    ) -> MctpBaseLibResult<Self> {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(hdr, completion_code as uint8_t, entries)
+    }
+
+    /// Response to a request for a message type the endpoint doesn't support
+    pub fn message_type_not_supported(req: Request) -> Self {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self {
+            hdr,
+            completion_code: MESSAGE_TYPE_NOT_SUPPORTED,
+            entries: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{
+        enums::{CommandCode, ResponseCode},
+        ControlMsgReponseStatus,
+    };
+    use anyhow::Result;
+
+    fn request_hdr() -> ControlMsgHeader {
+        ControlMsgHeader::new(CommandCode::GetMCTPVersionSupport, 0, false, true, false)
+    }
+
+    #[test]
+    fn test_response_round_trip() -> Result<()> {
+        let req = Request::new(request_hdr(), MCTP_BASE_SPEC_MSG_TYPE);
+        let entries = vec![
+            VersionEntry::from_version(1, 3, 1)?,
+            VersionEntry::from_version(1, 2, 0)?,
+        ];
+        let resp = Response::from(req, CompletionCode::Success, entries.clone())?;
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(
+            &bytes[3..],
+            &[0x00, 0x02, 0xF1, 0xF3, 0xF1, 0x00, 0xF1, 0xF2, 0xF0, 0x00]
+        );
+
+        let decoded = Response::try_from(bytes)?;
+        assert_eq!(decoded.entries, entries);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_entry_bcd_range() -> Result<()> {
+        let entry = VersionEntry::from_version(9, 10, 99)?;
+        assert_eq!(Bytes::from(entry).as_ref(), &[0xF9, 0x10, 0x99, 0x00]);
+        assert_eq!(VersionEntry::from_version(1, 3, 1)?, MCTP_BASE_SPEC_VERSION);
+
+        assert!(matches!(
+            VersionEntry::from_version(1, 100, 0),
+            Err(MctpBaseLibError::UnknownValue { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_unsupported_msg_type() -> Result<()> {
+        let req = Request::new(request_hdr(), 0x42);
+        let resp = Response::message_type_not_supported(req);
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(bytes.len(), 4);
+
+        let decoded = Response::try_from(bytes)?;
+        assert_eq!(decoded.completion_code, MESSAGE_TYPE_NOT_SUPPORTED);
+        assert!(decoded.entries.is_empty());
+        assert!(matches!(
+            decoded.is_success(),
+            Err(MctpBaseLibError::ErrorResponse {
+                command: CommandCode::GetMCTPVersionSupport,
+                completion_code: ResponseCode::CommandSpecific(MESSAGE_TYPE_NOT_SUPPORTED),
+            })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_truncated_entries() {
+        let bytes = Bytes::from(vec![0x00, 0x00, 0x04, 0x00, 0x02, 0xF1, 0xF3, 0xF1, 0x00]);
+        assert!(matches!(
+            Response::try_from(bytes),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+    }
+}
@@ -0,0 +1,285 @@
+//! Typed view of control messages: links every command code to its request and response types and
+//! decodes any control payload into the matching one.
+use bytes::Bytes;
+use std::fmt::Debug;
+
+use crate::{
+    base::{MctpBaseLibError, MctpBaseLibResult, TransportHeader},
+    control::{enums::CommandCode, models::ControlMsgHeader, *},
+};
+
+/// A control command and the request and response messages exchanged for it.
+pub trait ControlCommand {
+    const COMMAND_CODE: CommandCode;
+    type Request: TryFrom<Bytes, Error = MctpBaseLibError> + Into<Bytes> + Clone + Debug;
+    type Response: TryFrom<Bytes, Error = MctpBaseLibError>
+        + Into<Bytes>
+        + ControlMsgReponseStatus
+        + Clone
+        + Debug;
+
+    /// Decodes the request carried by `payload`
+    fn request(payload: &ControlPayload) -> MctpBaseLibResult<Self::Request> {
+        Self::Request::try_from(payload.payload.clone())
+    }
+
+    /// Decodes the response carried by `payload`
+    fn response(payload: &ControlPayload) -> MctpBaseLibResult<Self::Response> {
+        Self::Response::try_from(payload.payload.clone())
+    }
+}
+
+macro_rules! control_commands {
+    ($($(#[$doc:meta])* $command:ident => $module:ident,)*) => {
+        $(
+            $(#[$doc])*
+            #[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+            pub struct $command;
+
+            impl ControlCommand for $command {
+                const COMMAND_CODE: CommandCode = CommandCode::$command;
+                type Request = $module::Request;
+                type Response = $module::Response;
+            }
+        )*
+
+        /// A decoded control request
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum ControlRequest {
+            $($command($module::Request),)*
+        }
+
+        impl ControlRequest {
+            fn decode(command_code: CommandCode, body: Bytes) -> MctpBaseLibResult<Self> {
+                match command_code {
+                    $(CommandCode::$command => Ok(Self::$command($module::Request::try_from(body)?)),)*
+                    command => Err(MctpBaseLibError::UnsupportedCommand { command }),
+                }
+            }
+
+            pub fn hdr(&self) -> ControlMsgHeader {
+                match self {
+                    $(Self::$command(msg) => msg.hdr,)*
+                }
+            }
+        }
+
+        impl From<ControlRequest> for Bytes {
+            fn from(t: ControlRequest) -> Self {
+                match t {
+                    $(ControlRequest::$command(msg) => msg.into(),)*
+                }
+            }
+        }
+
+        /// A decoded control response
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum ControlResponse {
+            $($command($module::Response),)*
+        }
+
+        impl ControlResponse {
+            fn decode(command_code: CommandCode, body: Bytes) -> MctpBaseLibResult<Self> {
+                match command_code {
+                    $(CommandCode::$command => Ok(Self::$command($module::Response::try_from(body)?)),)*
+                    command => Err(MctpBaseLibError::UnsupportedCommand { command }),
+                }
+            }
+
+            pub fn hdr(&self) -> ControlMsgHeader {
+                match self {
+                    $(Self::$command(msg) => msg.hdr,)*
+                }
+            }
+
+            /// The completion code as received, including command specific codes
+            pub fn response_code(&self) -> ResponseCode {
+                match self {
+                    $(Self::$command(msg) => msg.response_code(),)*
+                }
+            }
+
+            #[deprecated(note = "loses command specific codes, use `response_code` instead")]
+            pub fn completion_code(&self) -> CompletionCode {
+                match self {
+                    $(Self::$command(msg) => {
+                        #[allow(deprecated)]
+                        msg.completion_code()
+                    })*
+                }
+            }
+        }
+
+        impl From<ControlResponse> for Bytes {
+            fn from(t: ControlResponse) -> Self {
+                match t {
+                    $(ControlResponse::$command(msg) => msg.into(),)*
+                }
+            }
+        }
+    };
+}
+
+control_commands! {
+    SetEndpointID => set_eid,
+    GetEndpointID => get_eid,
+    GetEndpointUUID => get_uuid,
+    GetMCTPVersionSupport => get_version_support,
+    GetMessageTypeSupport => get_message_type_support,
+    GetVendorDefinedMessageSupport => get_vendor_message_support,
+    ResolveEndpointID => resolve_eid,
+    AllocateEndpointIDs => allocate_eids,
+    RoutingInformationUpdate => routing_info_update,
+    GetRoutingTableEntries => get_routing_table,
+    PrepareForEndpointDiscovery => prepare_endpoint_discovery,
+    EndpointDiscovery => endpoint_discovery,
+    DiscoveryNotify => discovery_notify,
+    GetNetworkID => get_network_id,
+    QueryHop => query_hop,
+    ResolveUUID => resolve_uuid,
+    QueryRateLimit => query_rate_limit,
+    RequestTXRateLimit => request_tx_rate_limit,
+    UpdateRateLimit => update_rate_limit,
+    QuerySupportedInterfaces => query_supported_interfaces,
+}
+
+/// Any control message, decoded according to its command code and Rq bit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ControlMessage {
+    Request(ControlRequest),
+    Response(ControlResponse),
+}
+
+impl ControlMessage {
+    pub fn hdr(&self) -> ControlMsgHeader {
+        match self {
+            Self::Request(req) => req.hdr(),
+            Self::Response(resp) => resp.hdr(),
+        }
+    }
+
+    pub fn command_code(&self) -> CommandCode {
+        self.hdr().command_code
+    }
+
+    pub fn is_request(&self) -> bool {
+        matches!(self, Self::Request(_))
+    }
+
+    /// Wraps the message in a control payload sent with the transport header `hdr`
+    pub fn into_payload(self, hdr: TransportHeader) -> ControlPayload {
+        let control_hdr = self.hdr();
+        ControlPayload::new(hdr, control_hdr, self)
+    }
+}
+
+impl From<ControlMessage> for Bytes {
+    fn from(t: ControlMessage) -> Self {
+        match t {
+            ControlMessage::Request(req) => req.into(),
+            ControlMessage::Response(resp) => resp.into(),
+        }
+    }
+}
+
+impl TryFrom<&ControlPayload> for ControlMessage {
+    type Error = MctpBaseLibError;
+    fn try_from(payload: &ControlPayload) -> std::result::Result<Self, Self::Error> {
+        let command_code = payload.control_hdr.command_code;
+        let body = payload.payload.clone();
+        if payload.control_hdr.rq() != 0 {
+            Ok(Self::Request(ControlRequest::decode(command_code, body)?))
+        } else {
+            Ok(Self::Response(ControlResponse::decode(command_code, body)?))
+        }
+    }
+}
+
+impl TryFrom<ControlPayload> for ControlMessage {
+    type Error = MctpBaseLibError;
+    fn try_from(payload: ControlPayload) -> std::result::Result<Self, Self::Error> {
+        Self::try_from(&payload)
+    }
+}
+
+impl TryFrom<Bytes> for ControlMessage {
+    type Error = MctpBaseLibError;
+    fn try_from(bytes: Bytes) -> std::result::Result<Self, Self::Error> {
+        Self::try_from(ControlPayload::try_from(bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::models::RateLimit;
+    use anyhow::Result;
+
+    fn transport_header() -> TransportHeader {
+        TransportHeader::builder()
+            .src_eid(0x08)
+            .dst_eid(0x20)
+            .msg_tag(1)
+            .tag_owner(true)
+            .start_of_msg(true)
+            .end_of_msg(true)
+            .build()
+    }
+
+    #[test]
+    fn test_decode_request_and_response() -> Result<()> {
+        let hdr = ControlMsgHeader::new(CommandCode::RequestTXRateLimit, 3, false, true, false);
+        let req = request_tx_rate_limit::Request::new(hdr, RateLimit::new(4, 100));
+        let payload = ControlPayload::new(transport_header(), hdr, req);
+
+        let msg = ControlMessage::try_from(Bytes::from(payload.clone()))?;
+        assert_eq!(
+            msg,
+            ControlMessage::Request(ControlRequest::RequestTXRateLimit(req))
+        );
+        assert_eq!(RequestTXRateLimit::request(&payload)?, req);
+
+        let resp = request_tx_rate_limit::Response::from(
+            req,
+            CompletionCode::Success,
+            RateLimit::new(4, 50),
+        );
+        let resp_payload = ControlMessage::Response(ControlResponse::RequestTXRateLimit(resp))
+            .into_payload(payload.hdr.create_response());
+        let msg = ControlMessage::try_from(Bytes::from(resp_payload))?;
+        assert!(!msg.is_request());
+        assert_eq!(msg.command_code(), CommandCode::RequestTXRateLimit);
+        match msg {
+            ControlMessage::Response(ControlResponse::RequestTXRateLimit(decoded)) => {
+                assert_eq!(decoded, resp)
+            }
+            other => panic!("decoded the wrong message: {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_code_keeps_command_specific_codes() {
+        use get_version_support::MESSAGE_TYPE_NOT_SUPPORTED;
+
+        let hdr = ControlMsgHeader::new(CommandCode::GetMCTPVersionSupport, 3, false, true, false);
+        let req = get_version_support::Request::new(hdr, 0x42);
+        let resp = ControlResponse::GetMCTPVersionSupport(
+            get_version_support::Response::message_type_not_supported(req),
+        );
+        assert_eq!(
+            resp.response_code(),
+            ResponseCode::CommandSpecific(MESSAGE_TYPE_NOT_SUPPORTED)
+        );
+    }
+
+    #[test]
+    fn test_decode_unsupported_command() {
+        let bytes = Bytes::from(vec![0x01, 0x20, 0x08, 0xc8, 0x00, 0x80, 0xF0]);
+        assert!(matches!(
+            ControlMessage::try_from(bytes),
+            Err(MctpBaseLibError::UnsupportedCommand { .. })
+        ));
+    }
+}
@@ -1,4 +1,5 @@
-use bytes::Bytes;
+use anyhow::Error;
+use bytes::{Bytes, BytesMut};
 use c2rust_bitfields::BitfieldStruct;
 use cascade::cascade;
 use mctp_emu_derive::*;
@@ -7,12 +8,12 @@ use std::convert::From;
 
 use crate::{
     base::*,
-    control::{CommandCode, CompletionCode, ControlPayload},
+    control::{CommandCode, CompletionCode},
 };
 
 pub(crate) const SIZEOF_CONTROL_HDR: uint8_t = 3;
 
-#[derive(Copy, Clone, BitfieldStruct, Debug, PartialEq, Eq, Default)]
+#[derive(Copy, Clone, BitfieldStruct, BitfieldView, PartialEq, Eq, Default)]
 #[add_binary_derives]
 #[repr(C, packed)]
 pub struct ControlMsgHeader {
@@ -53,6 +54,41 @@ impl From<TransportHeader> for ControlMsgHeader {
     }
 }
 
+/// A transmit rate limit: bursts of up to `burst_size` packets, refilled at `max_rate` packets per
+/// second. A `max_rate` of zero means the transmitter isn't limited. Shared by the rate limiting
+/// commands.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct RateLimit {
+    pub burst_size: u32,
+    pub max_rate: u32,
+}
+
+impl RateLimit {
+    pub fn new(burst_size: u32, max_rate: u32) -> Self {
+        Self {
+            burst_size,
+            max_rate,
+        }
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.max_rate != 0
+    }
+}
+
+impl WireCodec for RateLimit {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        let burst_size = u32::decode_from(buf)?;
+        let max_rate = u32::decode_from(buf)?;
+        Ok(Self::new(burst_size, max_rate))
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        self.burst_size.encode_into(buf);
+        self.max_rate.encode_into(buf);
+    }
+}
+
 #[derive(Copy, Clone, BitfieldStruct, Debug, PartialEq, Eq, Default)]
 #[add_from_control_payload_derives]
 #[repr(C, packed)]
@@ -60,7 +96,7 @@ pub struct EmptyRequest {
     pub hdr: ControlMsgHeader,
 }
 
-#[derive(Copy, Clone, BitfieldStruct, Debug, PartialEq, Eq, Default)]
+#[derive(Copy, Clone, BitfieldStruct, Debug, PartialEq, Eq, Default, AddControlMsgResponse)]
 #[add_from_control_payload_derives]
 #[repr(C, packed)]
 pub struct EmptyResponse {
@@ -83,23 +119,8 @@ impl EmptyResponse {
     }
 }
 
-// pub mod discovery_notify {
-//     use crate::control::models::{EmptyRequest, EmptyResponse};
-//
-//     // Has no additional
-//     pub type Request = EmptyRequest;
-//     pub type Response = EmptyResponse;
-// }
-//
-// pub mod endpoint_discovery {40
-//     use crate::control::models::{EmptyRequest, EmptyResponse};
-//
-//     // Has no additional
-//     pub type Request = EmptyRequest;
-//     pub type Response = EmptyResponse;
-// }
-
 #[cfg(test)]
+#[allow(non_snake_case)]
 mod tests {
     use super::*;
     use anyhow::Result;
@@ -123,11 +144,65 @@ mod tests {
         let ctrl_hdr_bytes = Bytes::from(ctrl_hdr);
 
         let resp = EmptyResponse::new(ctrl_hdr, CompletionCode::Success as u8);
-        let bytes: Bytes = Bytes::try_from(resp).context("Failed to unmarshal buffer")?;
+        let bytes: Bytes = Bytes::from(resp);
 
         assert_eq!(ctrl_hdr_bytes.len(), 3);
         assert_eq!(bytes.len(), 4);
 
         Ok(())
     }
+
+    #[test]
+    fn test_ControlMsgHeader_wire_layout() -> Result<()> {
+        let ctrl_hdr = ControlMsgHeader::new(CommandCode::GetEndpointID, 5, false, true, false);
+        let bytes = Bytes::from(ctrl_hdr);
+        assert_eq!(bytes.as_ref(), &[0x00, 0x85, 0x02]);
+        assert_eq!(ControlMsgHeader::try_from(bytes)?, ctrl_hdr);
+
+        Ok(())
+    }
+
+    #[derive(Copy, Clone, BitfieldStruct, Debug, PartialEq, Eq, Default)]
+    #[add_binary_derives]
+    #[repr(C, packed)]
+    struct MixedEndianMessage {
+        #[bitfield(name = "low", ty = "uint8_t", bits = "0..=3")]
+        #[bitfield(name = "high", ty = "uint8_t", bits = "4..=7")]
+        low_high: [u8; 1],
+        #[wire(le)]
+        little: u16,
+        #[wire(be)]
+        big: u32,
+        default_order: u16,
+    }
+
+    #[test]
+    fn test_field_byte_order() -> Result<()> {
+        let mut msg = MixedEndianMessage {
+            little: 0x1234,
+            big: 0x01020304,
+            default_order: 0xABCD,
+            ..Default::default()
+        };
+        msg.set_low(0x1);
+        msg.set_high(0xA);
+
+        let bytes = Bytes::from(msg);
+        assert_eq!(
+            bytes.as_ref(),
+            &[0xA1, 0x34, 0x12, 0x01, 0x02, 0x03, 0x04, 0xAB, 0xCD]
+        );
+        assert_eq!(MixedEndianMessage::try_from(bytes)?, msg);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncated_fixed_message() {
+        let bytes = Bytes::from_static(&[0x00, 0x85]);
+        assert!(matches!(
+            ControlMsgHeader::try_from(bytes),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+    }
 }
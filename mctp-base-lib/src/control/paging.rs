@@ -0,0 +1,77 @@
+use crate::{base::*, control::models::SIZEOF_CONTROL_HDR};
+
+/// Entry handle returned in the last response of a paged command
+pub const NO_MORE_ENTRIES: uint8_t = 0xFF;
+
+/// Size of everything in a paged response besides the entries (message type, control header,
+/// completion code, next entry handle and number of entries)
+const RESPONSE_OVERHEAD: usize = SIZEOF_CONTROL_HDR as usize + 3;
+
+/// Picks the page of `entries` starting at `entry_handle` (the index of its first entry), fitting
+/// as many entries as possible into a response of `max_msg_size` bytes and its count byte. Returns
+/// the handle of the next page (or [`NO_MORE_ENTRIES`]) along with the entries, or `None` when the
+/// handle is past the end. Handle 0 of an empty list is an empty last page.
+pub fn page_entries<T: Clone>(
+    entries: &[T],
+    entry_handle: uint8_t,
+    max_msg_size: usize,
+    encoded_len: fn(&T) -> usize,
+) -> Option<(uint8_t, Vec<T>)> {
+    let first = entry_handle as usize;
+    if first >= entries.len() && !(first == 0 && entries.is_empty()) {
+        return None;
+    }
+
+    let mut size = RESPONSE_OVERHEAD;
+    let mut page = Vec::new();
+    for entry in entries[first..].iter().take(u8::MAX as usize) {
+        // Always return at least one entry so the requester makes progress
+        if !page.is_empty() && size + encoded_len(entry) > max_msg_size {
+            break;
+        }
+        size += encoded_len(entry);
+        page.push(entry.clone());
+    }
+
+    let next = first + page.len();
+    let next_entry_handle = if next >= entries.len() || next >= NO_MORE_ENTRIES as usize {
+        NO_MORE_ENTRIES
+    } else {
+        next as uint8_t
+    };
+    Some((next_entry_handle, page))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_entries() {
+        let entries: Vec<u8> = (0..10).collect();
+        let len = |_: &u8| 4;
+        let max_msg_size = RESPONSE_OVERHEAD + 3 * 4;
+
+        assert_eq!(
+            page_entries(&entries, 0, max_msg_size, len),
+            Some((3, vec![0, 1, 2]))
+        );
+        assert_eq!(
+            page_entries(&entries, 9, max_msg_size, len),
+            Some((NO_MORE_ENTRIES, vec![9]))
+        );
+        assert_eq!(page_entries(&entries, 10, max_msg_size, len), None);
+
+        // an entry larger than the message still goes out on its own
+        assert_eq!(page_entries(&entries, 0, 0, len), Some((1, vec![0])));
+        assert_eq!(
+            page_entries::<u8>(&[], 0, max_msg_size, len),
+            Some((NO_MORE_ENTRIES, vec![]))
+        );
+
+        // no more entries than the count byte can describe, however small they are
+        let entries = vec![0u8; 300];
+        let (_, page) = page_entries(&entries, 0, usize::MAX, |_| 0).unwrap();
+        assert_eq!(page.len(), 255);
+    }
+}
@@ -0,0 +1,7 @@
+//! Prepare for Endpoint Discovery: broadcast by the bus owner to clear the "discovered" flag of
+//! every endpoint on the bus before running Endpoint Discovery.
+use crate::control::models::{EmptyRequest, EmptyResponse};
+
+// Has no additional fields
+pub type Request = EmptyRequest;
+pub type Response = EmptyResponse;
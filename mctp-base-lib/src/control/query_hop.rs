@@ -0,0 +1,128 @@
+use anyhow::Error;
+
+use crate::{
+    base::*,
+    control::{models::ControlMsgHeader, CompletionCode},
+};
+
+/// Next bridge EID reported when the target is reachable without going through another bridge
+pub const NO_NEXT_BRIDGE: uint8_t = 0x00;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+    pub target_eid: uint8_t,
+    pub message_type: uint8_t,
+}
+
+impl Request {
+    pub fn new(hdr: ControlMsgHeader, target_eid: uint8_t, message_type: uint8_t) -> Self {
+        Self {
+            hdr,
+            target_eid,
+            message_type,
+        }
+    }
+}
+
+/// The transmission unit sizes are sent big-endian.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    c2rust_bitfields::BitfieldStruct,
+    mctp_emu_derive::AddControlMsgResponse,
+)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    /// EID of the next bridge on the path to the target or [`NO_NEXT_BRIDGE`]
+    pub next_bridge_eid: uint8_t,
+    pub message_type: uint8_t,
+    #[wire(be)]
+    max_incoming_unit: u16,
+    #[wire(be)]
+    max_outgoing_unit: u16,
+}
+
+impl Response {
+    pub fn new(
+        hdr: ControlMsgHeader,
+        completion_code: uint8_t,
+        next_bridge_eid: uint8_t,
+        message_type: uint8_t,
+        max_incoming_unit: u16,
+        max_outgoing_unit: u16,
+    ) -> Self {
+        Self {
+            hdr,
+            completion_code,
+            next_bridge_eid,
+            message_type,
+            max_incoming_unit,
+            max_outgoing_unit,
+        }
+    }
+
+    pub fn from(
+        req: Request,
+        completion_code: CompletionCode,
+        next_bridge_eid: uint8_t,
+        max_incoming_unit: u16,
+        max_outgoing_unit: u16,
+    ) -> Self {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(
+            hdr,
+            completion_code as uint8_t,
+            next_bridge_eid,
+            req.message_type,
+            max_incoming_unit,
+            max_outgoing_unit,
+        )
+    }
+
+    /// Largest transmission unit the bridge accepts on the link the request came in on
+    pub fn max_incoming_unit(&self) -> u16 {
+        self.max_incoming_unit
+    }
+
+    /// Largest transmission unit the bridge uses when forwarding towards the target
+    pub fn max_outgoing_unit(&self) -> u16 {
+        self.max_outgoing_unit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::enums::{CommandCode, MessageType};
+    use anyhow::Result;
+    use bytes::Bytes;
+
+    #[test]
+    fn test_response_round_trip() -> Result<()> {
+        let hdr = ControlMsgHeader::new(CommandCode::QueryHop, 0, false, true, false);
+        let req = Request::new(hdr, 0x30, MessageType::Pldm as uint8_t);
+        let resp = Response::from(req, CompletionCode::Success, 0x10, 256, 64);
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(&bytes[3..], &[0x00, 0x10, 0x01, 0x01, 0x00, 0x00, 0x40]);
+
+        let decoded = Response::try_from(bytes)?;
+        assert_eq!(decoded.next_bridge_eid, 0x10);
+        assert_eq!(decoded.message_type, MessageType::Pldm as uint8_t);
+        assert_eq!(decoded.max_incoming_unit(), 256);
+        assert_eq!(decoded.max_outgoing_unit(), 64);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use anyhow::Error;
+use bytes::Bytes;
+
+use crate::{
+    base::*,
+    control::{models::*, CompletionCode},
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+}
+
+/// Rate limiting capabilities of the responder. Every field is sent big-endian and only present
+/// when the completion code is `Success`.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    mctp_emu_derive::AddControlMsgResponse,
+    mctp_emu_derive::WireMessage,
+)]
+#[wire(control_payload)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    /// Size of the receive buffer, in bytes
+    #[wire(on_success)]
+    pub receive_buffer_size: u32,
+    /// Highest rate the responder can receive at, in packets per second
+    #[wire(on_success)]
+    pub max_receive_rate: u32,
+    /// Largest burst and fastest rate the responder's transmitter can be limited to
+    #[wire(on_success)]
+    pub max_tx_limit: RateLimit,
+    /// Slowest rate the responder's transmitter can be limited to, in packets per second
+    #[wire(on_success)]
+    pub min_tx_rate: u32,
+    /// Limit currently applied to the responder's transmitter for the requester
+    #[wire(on_success)]
+    pub present_tx_limit: RateLimit,
+}
+
+impl Response {
+    pub fn new(
+        hdr: ControlMsgHeader,
+        completion_code: uint8_t,
+        receive_buffer_size: u32,
+        max_receive_rate: u32,
+        max_tx_limit: RateLimit,
+        min_tx_rate: u32,
+        present_tx_limit: RateLimit,
+    ) -> Self {
+        Self {
+            hdr,
+            completion_code,
+            receive_buffer_size,
+            max_receive_rate,
+            max_tx_limit,
+            min_tx_rate,
+            present_tx_limit,
+        }
+    }
+
+    pub fn from(
+        req: Request,
+        completion_code: CompletionCode,
+        receive_buffer_size: u32,
+        max_receive_rate: u32,
+        max_tx_limit: RateLimit,
+        min_tx_rate: u32,
+        present_tx_limit: RateLimit,
+    ) -> Self {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(
+            hdr,
+            completion_code as uint8_t,
+            receive_buffer_size,
+            max_receive_rate,
+            max_tx_limit,
+            min_tx_rate,
+            present_tx_limit,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::enums::CommandCode;
+    use anyhow::Result;
+
+    #[test]
+    fn test_response_round_trip() -> Result<()> {
+        let hdr = ControlMsgHeader::new(CommandCode::QueryRateLimit, 0, false, true, false);
+        let resp = Response::from(
+            Request { hdr },
+            CompletionCode::Success,
+            4096,
+            1000,
+            RateLimit::new(16, 2000),
+            10,
+            RateLimit::default(),
+        );
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(bytes.len(), 4 + 7 * 4);
+        assert_eq!(&bytes[4..8], &[0x00, 0x00, 0x10, 0x00]);
+        assert_eq!(Response::try_from(bytes)?, resp);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::Error;
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::{
+    base::*,
+    control::{enums::*, models::*, *},
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+}
+
+/// A physical interface of the responder and the EID it is reachable at on that interface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct SupportedInterface {
+    pub binding_type: PhysicalTransportBinding,
+    pub medium_type: PhysicalMediumIdentifier,
+    pub eid: uint8_t,
+}
+
+impl SupportedInterface {
+    pub fn new(
+        binding_type: PhysicalTransportBinding,
+        medium_type: PhysicalMediumIdentifier,
+        eid: uint8_t,
+    ) -> Self {
+        Self {
+            binding_type,
+            medium_type,
+            eid,
+        }
+    }
+}
+
+impl WireCodec for SupportedInterface {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        let binding_type = PhysicalTransportBinding::from(u8::decode_from(buf)?);
+        let medium_type = PhysicalMediumIdentifier::from(u8::decode_from(buf)?);
+        let eid = u8::decode_from(buf)?;
+        Ok(Self::new(binding_type, medium_type, eid))
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u8(self.binding_type as uint8_t);
+        buf.put_u8(self.medium_type as uint8_t);
+        buf.put_u8(self.eid);
+    }
+}
+
+/// The interfaces are prefixed by their count and only present when the completion code is
+/// `Success`.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    mctp_emu_derive::AddControlMsgResponse,
+    mctp_emu_derive::WireMessage,
+)]
+#[wire(control_payload)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    #[wire(on_success, count_prefixed)]
+    pub interfaces: Vec<SupportedInterface>,
+}
+
+impl Response {
+    pub fn new(
+        hdr: ControlMsgHeader,
+        completion_code: uint8_t,
+        interfaces: Vec<SupportedInterface>,
+    ) -> MctpBaseLibResult<Self> {
+        check_count(interfaces.len())?;
+        Ok(Self {
+            hdr,
+            completion_code,
+            interfaces,
+        })
+    }
+
+    pub fn from(
+        req: Request,
+        completion_code: CompletionCode,
+        interfaces: Vec<SupportedInterface>,
+    ) -> MctpBaseLibResult<Self> {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(hdr, completion_code as uint8_t, interfaces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_response_round_trip() -> Result<()> {
+        let hdr =
+            ControlMsgHeader::new(CommandCode::QuerySupportedInterfaces, 0, false, true, false);
+        let interfaces = vec![
+            SupportedInterface::new(
+                PhysicalTransportBinding::MCTPoverSMBus,
+                PhysicalMediumIdentifier::SMBUS_2_0_100khz,
+                0x20,
+            ),
+            SupportedInterface::new(
+                PhysicalTransportBinding::MCTPoverPcieVdm,
+                PhysicalMediumIdentifier::PCIeRev_3,
+                0x21,
+            ),
+        ];
+        let resp = Response::from(Request { hdr }, CompletionCode::Success, interfaces)?;
+
+        let bytes = Bytes::from(resp.clone());
+        assert_eq!(bytes[4], 2);
+        assert_eq!(bytes.len(), 5 + 2 * 3);
+        assert_eq!(Response::try_from(bytes)?, resp);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_truncated() {
+        let bytes = Bytes::from(vec![0x00, 0x00, 0x14, 0x00, 0x02, 0x01, 0x01, 0x20]);
+        assert!(matches!(
+            Response::try_from(bytes),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+    }
+}
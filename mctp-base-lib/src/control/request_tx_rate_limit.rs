@@ -0,0 +1,103 @@
+use anyhow::Error;
+use bytes::Bytes;
+
+use crate::{
+    base::*,
+    control::{models::*, CompletionCode},
+};
+
+/// Asks the responder to limit what it transmits to the requester.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, mctp_emu_derive::WireMessage)]
+#[wire(control_payload)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+    pub tx_limit: RateLimit,
+}
+
+impl Request {
+    pub fn new(hdr: ControlMsgHeader, tx_limit: RateLimit) -> Self {
+        Self { hdr, tx_limit }
+    }
+}
+
+/// The limit the responder applied, which may differ from the requested one when it is outside of
+/// the supported range. Only present when the completion code is `Success`.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    mctp_emu_derive::AddControlMsgResponse,
+    mctp_emu_derive::WireMessage,
+)]
+#[wire(control_payload)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    #[wire(on_success)]
+    pub present_tx_limit: RateLimit,
+}
+
+impl Response {
+    pub fn new(
+        hdr: ControlMsgHeader,
+        completion_code: uint8_t,
+        present_tx_limit: RateLimit,
+    ) -> Self {
+        Self {
+            hdr,
+            completion_code,
+            present_tx_limit,
+        }
+    }
+
+    pub fn from(
+        req: Request,
+        completion_code: CompletionCode,
+        present_tx_limit: RateLimit,
+    ) -> Self {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(hdr, completion_code as uint8_t, present_tx_limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::enums::CommandCode;
+    use anyhow::Result;
+
+    #[test]
+    fn test_request_round_trip() -> Result<()> {
+        let hdr = ControlMsgHeader::new(CommandCode::RequestTXRateLimit, 0, false, true, false);
+        let req = Request::new(hdr, RateLimit::new(4, 100));
+
+        let bytes = Bytes::from(req);
+        assert_eq!(
+            &bytes[3..],
+            &[0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x64]
+        );
+        assert_eq!(Request::try_from(bytes)?, req);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_request_wrong_size() {
+        let bytes = Bytes::from(vec![0x00, 0x80, 0x12, 0x00, 0x00, 0x00, 0x04]);
+        assert!(matches!(
+            Request::try_from(bytes),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+
+        let mut bytes = vec![0x00, 0x80, 0x12];
+        bytes.extend_from_slice(&[0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x64, 0x00]);
+        assert!(matches!(
+            Request::try_from(Bytes::from(bytes)),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::Error;
+use bytes::Bytes;
+
+use crate::{
+    base::*,
+    control::{models::ControlMsgHeader, CompletionCode},
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+    pub target_eid: uint8_t,
+}
+
+impl Request {
+    pub fn new(hdr: ControlMsgHeader, target_eid: uint8_t) -> Self {
+        Self { hdr, target_eid }
+    }
+}
+
+/// The physical address is medium specific and takes up the rest of the message. The bridge EID
+/// and physical address are only present when the completion code is `Success`.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    mctp_emu_derive::AddControlMsgResponse,
+    mctp_emu_derive::WireMessage,
+)]
+#[wire(control_payload)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    /// EID of the bridge to use to reach the target (the target EID itself when on the same bus)
+    #[wire(on_success)]
+    pub bridge_eid: uint8_t,
+    #[wire(on_success, rest)]
+    pub physical_address: Bytes,
+}
+
+impl Response {
+    pub fn new<T: Into<Bytes>>(
+        hdr: ControlMsgHeader,
+        completion_code: uint8_t,
+        bridge_eid: uint8_t,
+        physical_address: T,
+    ) -> Self {
+        Self {
+            hdr,
+            completion_code,
+            bridge_eid,
+            physical_address: physical_address.into(),
+        }
+    }
+
+    pub fn from<T: Into<Bytes>>(
+        req: Request,
+        completion_code: CompletionCode,
+        bridge_eid: uint8_t,
+        physical_address: T,
+    ) -> Self {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(
+            hdr,
+            completion_code as uint8_t,
+            bridge_eid,
+            physical_address,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::enums::CommandCode;
+    use anyhow::Result;
+
+    #[test]
+    fn test_response_round_trip() -> Result<()> {
+        let hdr = ControlMsgHeader::new(CommandCode::ResolveEndpointID, 0, false, true, false);
+        let resp = Response::from(
+            Request::new(hdr, 0x20),
+            CompletionCode::Success,
+            0x10,
+            vec![0x32],
+        );
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(&bytes[3..], &[0x00, 0x10, 0x32]);
+
+        let decoded = Response::try_from(bytes)?;
+        assert_eq!(decoded.bridge_eid, 0x10);
+        assert_eq!(decoded.physical_address.as_ref(), &[0x32]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use anyhow::Error;
+use bytes::Bytes;
+use uuid::Uuid;
+
+use crate::{
+    base::*,
+    control::{models::*, paging::page_entries, CompletionCode},
+};
+
+pub use crate::control::paging::NO_MORE_ENTRIES;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[mctp_emu_derive::add_from_control_payload_derives]
+#[repr(C, packed)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+    /// UUID in RFC4122 (network) byte order
+    raw_uuid: [u8; 16],
+    /// Handle of the first entry to return, `0x00` for the first request
+    pub entry_handle: uint8_t,
+}
+
+impl Request {
+    pub fn new(hdr: ControlMsgHeader, uuid: Uuid, entry_handle: uint8_t) -> Self {
+        Self {
+            hdr,
+            raw_uuid: *uuid.as_bytes(),
+            entry_handle,
+        }
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        Uuid::from_bytes(self.raw_uuid)
+    }
+}
+
+/// An EID that was resolved for the requested UUID, with the physical address to reach it. The
+/// physical address is prefixed on the wire by its size.
+#[derive(Clone, Debug, PartialEq, Eq, Default, mctp_emu_derive::WireMessage)]
+pub struct UuidResolutionEntry {
+    pub eid: uint8_t,
+    #[wire(count_prefixed)]
+    pub physical_address: Bytes,
+}
+
+impl UuidResolutionEntry {
+    pub fn new<T: Into<Bytes>>(eid: uint8_t, physical_address: T) -> MctpBaseLibResult<Self> {
+        let physical_address = physical_address.into();
+        check_count(physical_address.len())?;
+        Ok(Self {
+            eid,
+            physical_address,
+        })
+    }
+
+    /// Number of bytes the entry takes up in a response
+    pub fn encoded_len(&self) -> usize {
+        2 + self.physical_address.len()
+    }
+}
+
+/// The next entry handle and entries are only present when the completion code is `Success`.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Default,
+    mctp_emu_derive::AddControlMsgResponse,
+    mctp_emu_derive::WireMessage,
+)]
+#[wire(control_payload)]
+pub struct Response {
+    pub hdr: ControlMsgHeader,
+    pub completion_code: uint8_t,
+    /// Handle to use for the next request or [`NO_MORE_ENTRIES`]
+    #[wire(on_success)]
+    pub next_entry_handle: uint8_t,
+    #[wire(on_success, count_prefixed)]
+    pub entries: Vec<UuidResolutionEntry>,
+}
+
+impl Response {
+    pub fn new(
+        hdr: ControlMsgHeader,
+        completion_code: uint8_t,
+        next_entry_handle: uint8_t,
+        entries: Vec<UuidResolutionEntry>,
+    ) -> MctpBaseLibResult<Self> {
+        check_count(entries.len())?;
+        Ok(Self {
+            hdr,
+            completion_code,
+            next_entry_handle,
+            entries,
+        })
+    }
+
+    pub fn from(
+        req: Request,
+        completion_code: CompletionCode,
+        next_entry_handle: uint8_t,
+        entries: Vec<UuidResolutionEntry>,
+    ) -> MctpBaseLibResult<Self> {
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        Self::new(hdr, completion_code as uint8_t, next_entry_handle, entries)
+    }
+
+    /// Builds the response for the page of `resolved` starting at the requested entry handle,
+    /// fitting as many entries as possible into a message of `max_msg_size` bytes. An unknown UUID
+    /// (no entries) is reported with `ErrorInvalidData`.
+    pub fn from_entries(
+        req: Request,
+        resolved: &[UuidResolutionEntry],
+        max_msg_size: usize,
+    ) -> Self {
+        let page = match resolved.is_empty() {
+            true => None,
+            false => page_entries(
+                resolved,
+                req.entry_handle,
+                max_msg_size,
+                UuidResolutionEntry::encoded_len,
+            ),
+        };
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        // a page never holds more entries than its count byte can describe
+        match page {
+            Some((next_entry_handle, entries)) => Self {
+                hdr,
+                completion_code: CompletionCode::Success as uint8_t,
+                next_entry_handle,
+                entries,
+            },
+            None => Self {
+                hdr,
+                completion_code: CompletionCode::ErrorInvalidData as uint8_t,
+                next_entry_handle: NO_MORE_ENTRIES,
+                entries: Vec::new(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::{enums::CommandCode, ControlMsgReponseStatus};
+    use anyhow::Result;
+
+    fn request(entry_handle: uint8_t) -> Request {
+        let hdr = ControlMsgHeader::new(CommandCode::ResolveUUID, 0, false, true, false);
+        Request::new(hdr, Uuid::from_u128(0x1234), entry_handle)
+    }
+
+    #[test]
+    fn test_request_round_trip() -> Result<()> {
+        let bytes = Bytes::from(request(2));
+        assert_eq!(bytes.len(), 20);
+        assert_eq!(bytes[19], 2);
+
+        let decoded = Request::try_from(bytes)?;
+        assert_eq!(decoded.uuid(), Uuid::from_u128(0x1234));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_round_trip() -> Result<()> {
+        let entries = vec![
+            UuidResolutionEntry::new(0x20, vec![0x30])?,
+            UuidResolutionEntry::new(0x48, vec![0x31])?,
+        ];
+        let resp = Response::from_entries(request(0), &entries, 64);
+
+        let bytes = Bytes::from(resp);
+        assert_eq!(
+            &bytes[3..],
+            &[0x00, 0xFF, 0x02, 0x20, 0x01, 0x30, 0x48, 0x01, 0x31]
+        );
+        assert_eq!(Response::try_from(bytes)?.entries, entries);
+
+        let resp = Response::from_entries(request(0), &entries, 9);
+        assert_eq!(resp.entries, entries[..1]);
+        assert_eq!(resp.next_entry_handle, 1);
+
+        let resp = Response::from_entries(request(0), &[], 64);
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidData);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_address_too_long() {
+        assert!(UuidResolutionEntry::new(0x20, vec![0x30; 255]).is_ok());
+        assert!(matches!(
+            UuidResolutionEntry::new(0x20, vec![0x30; 256]),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+    }
+}
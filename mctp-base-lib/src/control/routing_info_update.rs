@@ -0,0 +1,141 @@
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::{
+    base::*,
+    control::{
+        enums::RoutingEntryType,
+        models::{ControlMsgHeader, EmptyResponse},
+    },
+};
+
+/// The response only carries a completion code
+pub type Response = EmptyResponse;
+
+/// A single routing information update entry. The physical address is medium specific and is
+/// prefixed on the wire by its size.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct RoutingInfoEntry {
+    pub entry_type: RoutingEntryType,
+    pub starting_eid: uint8_t,
+    pub eid_range_size: uint8_t,
+    pub physical_address: Bytes,
+}
+
+impl RoutingInfoEntry {
+    pub fn new<T: Into<Bytes>>(
+        entry_type: RoutingEntryType,
+        starting_eid: uint8_t,
+        eid_range_size: uint8_t,
+        physical_address: T,
+    ) -> MctpBaseLibResult<Self> {
+        let physical_address = physical_address.into();
+        check_count(physical_address.len())?;
+        Ok(Self {
+            entry_type,
+            starting_eid,
+            eid_range_size,
+            physical_address,
+        })
+    }
+
+    /// Last EID covered by this entry
+    pub fn last_eid(&self) -> uint8_t {
+        self.starting_eid
+            .saturating_add(self.eid_range_size.max(1) - 1)
+    }
+}
+
+impl WireCodec for RoutingInfoEntry {
+    fn decode_from(buf: &mut Bytes) -> MctpBaseLibResult<Self> {
+        let entry_type = RoutingEntryType::from(u8::decode_from(buf)? >> 6);
+        let starting_eid = u8::decode_from(buf)?;
+        let eid_range_size = u8::decode_from(buf)?;
+        let address_size = u8::decode_from(buf)? as usize;
+        ensure_remaining(buf, address_size)?;
+        Self::new(
+            entry_type,
+            starting_eid,
+            eid_range_size,
+            buf.split_to(address_size),
+        )
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u8((self.entry_type as uint8_t) << 6);
+        buf.put_u8(self.starting_eid);
+        buf.put_u8(self.eid_range_size);
+        let count = encode_count(buf, self.physical_address.len());
+        buf.put_slice(&self.physical_address[..count]);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Default, mctp_emu_derive::WireMessage)]
+#[wire(control_payload)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+    #[wire(count_prefixed)]
+    pub entries: Vec<RoutingInfoEntry>,
+}
+
+impl Request {
+    pub fn new(hdr: ControlMsgHeader, entries: Vec<RoutingInfoEntry>) -> MctpBaseLibResult<Self> {
+        check_count(entries.len())?;
+        Ok(Self { hdr, entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::enums::CommandCode;
+    use anyhow::Result;
+
+    #[test]
+    fn test_request_round_trip() -> Result<()> {
+        let hdr =
+            ControlMsgHeader::new(CommandCode::RoutingInformationUpdate, 0, false, true, false);
+        let entries = vec![
+            RoutingInfoEntry::new(RoutingEntryType::SingleEndpoint, 0x20, 1, vec![0x30])?,
+            RoutingInfoEntry::new(
+                RoutingEntryType::BridgeAndDownstreamEids,
+                0x40,
+                8,
+                vec![0x31],
+            )?,
+        ];
+        let req = Request::new(hdr, entries.clone())?;
+
+        let bytes = Bytes::from(req);
+        assert_eq!(
+            &bytes[3..],
+            &[0x02, 0x00, 0x20, 0x01, 0x01, 0x30, 0x40, 0x40, 0x08, 0x01, 0x31]
+        );
+
+        let decoded = Request::try_from(bytes)?;
+        assert_eq!(decoded.entries, entries);
+        assert_eq!(decoded.entries[1].last_eid(), 0x47);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_address_too_long() {
+        assert!(
+            RoutingInfoEntry::new(RoutingEntryType::SingleEndpoint, 0x20, 1, vec![0x30; 255])
+                .is_ok()
+        );
+        assert!(matches!(
+            RoutingInfoEntry::new(RoutingEntryType::SingleEndpoint, 0x20, 1, vec![0x30; 256]),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+    }
+
+    #[test]
+    fn test_request_truncated_address() {
+        let bytes = Bytes::from(vec![0x00, 0x80, 0x09, 0x01, 0x00, 0x20, 0x01, 0x02, 0x30]);
+        assert!(matches!(
+            Request::try_from(bytes),
+            Err(MctpBaseLibError::InvalidPayloadSize { .. })
+        ));
+    }
+}
@@ -26,12 +26,25 @@ pub enum Operation {
     SetDiscoveredFlag = 3,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, c2rust_bitfields::BitfieldStruct)]
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Default,
+    c2rust_bitfields::BitfieldStruct,
+    mctp_emu_derive::BitfieldView,
+)]
 #[mctp_emu_derive::add_from_control_payload_derives]
 #[repr(C, packed)]
 pub struct Request {
     pub hdr: ControlMsgHeader,
-    #[bitfield(name = "operation", ty = "uint8_t", bits = "0..=1")]
+    #[bitfield(
+        name = "raw_operation",
+        enum_ty = "Operation",
+        ty = "uint8_t",
+        bits = "0..=1"
+    )]
     #[bitfield(name = "reserved", ty = "uint8_t", bits = "2..=7")]
     operation_reserved: [u8; 1],
     pub eid: uint8_t,
@@ -45,7 +58,7 @@ impl Request {
                 operation_reserved: [0; 1],
                 eid,
             };
-            ..set_operation(operation as uint8_t);
+            ..set_operation(operation);
         }
     }
 }
@@ -90,11 +103,11 @@ pub enum EidAllocationStatus {
 #[derive(
     Copy,
     Clone,
-    Debug,
     PartialEq,
     Eq,
     Default,
     c2rust_bitfields::BitfieldStruct,
+    mctp_emu_derive::BitfieldView,
     mctp_emu_derive::AddControlMsgResponse,
 )]
 #[mctp_emu_derive::add_from_control_payload_derives]
@@ -104,12 +117,17 @@ pub struct Response {
     pub completion_code: uint8_t,
     #[bitfield(
         name = "raw_eid_allocation_status",
-        field = "eid_allocation_status",
+        enum_ty = "EidAllocationStatus",
         ty = "uint8_t",
         bits = "0..=1"
     )]
     #[bitfield(name = "reserved1", ty = "uint8_t", bits = "2..=3")]
-    #[bitfield(name = "eid_assignment_status", ty = "uint8_t", bits = "4..=5")]
+    #[bitfield(
+        name = "raw_eid_assignment_status",
+        enum_ty = "EidAssignmentStatus",
+        ty = "uint8_t",
+        bits = "4..=5"
+    )]
     #[bitfield(name = "reserved2", ty = "uint8_t", bits = "6..=7")]
     eid_allocation_status_reserved1_eid_assignment_status_reserved2: [u8; 1],
     pub eid_setting: uint8_t,
@@ -133,8 +151,8 @@ impl Response {
                 eid_setting,
                 eid_pool_size,
             };
-            ..set_raw_eid_allocation_status(eid_allocation_status as u8);
-            ..set_eid_assignment_status(eid_assignment_status as u8);
+            ..set_eid_allocation_status(eid_allocation_status);
+            ..set_eid_assignment_status(eid_assignment_status);
         }
     }
 
@@ -157,8 +175,61 @@ impl Response {
             eid_pool_size,
         )
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use bytes::Bytes;
+
+    fn response() -> Response {
+        let hdr = ControlMsgHeader::new(CommandCode::SetEndpointID, 2, false, true, false);
+        Response::from(
+            Request::new(hdr, Operation::SetEid, 0x20),
+            CompletionCode::Success,
+            EidAllocationStatus::RequiresPoolAllocation,
+            EidAssignmentStatus::Accepted,
+            0x20,
+            4,
+        )
+    }
+
+    #[test]
+    fn test_typed_bitfields() -> Result<()> {
+        let mut resp = response();
+        assert_eq!(
+            resp.eid_allocation_status(),
+            EidAllocationStatus::RequiresPoolAllocation
+        );
+        assert_eq!(resp.eid_assignment_status(), EidAssignmentStatus::Accepted);
+
+        resp.set_eid_assignment_status(EidAssignmentStatus::Rejected);
+        let bytes = Bytes::from(resp);
+        assert_eq!(&bytes[3..], &[0x00, 0x11, 0x20, 0x04]);
+        assert_eq!(
+            Response::try_from(bytes)?.eid_assignment_status(),
+            EidAssignmentStatus::Rejected
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_lists_logical_fields() {
+        let resp = response();
+        let debug = format!("{:?}", resp);
+        assert!(debug.contains("eid_allocation_status: RequiresPoolAllocation"));
+        assert!(debug.contains("eid_assignment_status: Accepted"));
+        assert!(debug.contains("instance_id: 2"));
+        assert!(debug.contains("command_code: SetEndpointID"));
+        assert!(!debug.contains("eid_allocation_status_reserved1"));
 
-    pub fn eid_allocation_status(&self) -> EidAllocationStatus {
-        EidAllocationStatus::from(self.raw_eid_allocation_status())
+        let display = resp.hdr.to_string();
+        assert_eq!(
+            display,
+            "ControlMsgHeader msg_type=0 integrity_check=0 instance_id=2 rsvd=0 d_bit=0 rq=0 \
+             command_code=SetEndpointID"
+        );
     }
 }
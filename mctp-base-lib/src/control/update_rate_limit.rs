@@ -0,0 +1,80 @@
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    base::*,
+    control::{
+        models::{ControlMsgHeader, EmptyResponse},
+        ControlPayload,
+    },
+};
+
+/// The response only carries a completion code
+pub type Response = EmptyResponse;
+
+/// Tells a peer the rate the requester can now receive at, so the peer can update the limit it
+/// applies when transmitting to the requester.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct Request {
+    pub hdr: ControlMsgHeader,
+    /// Highest rate the requester can receive at, in packets per second
+    pub max_receive_rate: u32,
+}
+
+impl Request {
+    pub fn new(hdr: ControlMsgHeader, max_receive_rate: u32) -> Self {
+        Self {
+            hdr,
+            max_receive_rate,
+        }
+    }
+}
+
+impl From<Request> for Bytes {
+    fn from(t: Request) -> Self {
+        let mut buf = BytesMut::new();
+        t.hdr.encode_into(&mut buf);
+        t.max_receive_rate.encode_into(&mut buf);
+        buf.freeze()
+    }
+}
+
+impl From<Request> for Vec<u8> {
+    fn from(t: Request) -> Self {
+        Bytes::from(t).to_vec()
+    }
+}
+
+impl TryFrom<Bytes> for Request {
+    type Error = MctpBaseLibError;
+    fn try_from(mut bytes: Bytes) -> std::result::Result<Self, Self::Error> {
+        let hdr = ControlMsgHeader::decode_from(&mut bytes)?;
+        let max_receive_rate = u32::decode_from(&mut bytes)?;
+        Ok(Self::new(hdr, max_receive_rate))
+    }
+}
+
+impl TryFrom<ControlPayload> for Request {
+    type Error = MctpBaseLibError;
+    fn try_from(msg: ControlPayload) -> std::result::Result<Self, Self::Error> {
+        Self::try_from(msg.payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::enums::CommandCode;
+    use anyhow::Result;
+
+    #[test]
+    fn test_request_round_trip() -> Result<()> {
+        let hdr = ControlMsgHeader::new(CommandCode::UpdateRateLimit, 0, false, true, false);
+        let req = Request::new(hdr, 0x0102_0304);
+
+        let bytes = Bytes::from(req);
+        assert_eq!(&bytes[3..], &[0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(Request::try_from(bytes)?, req);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+//! Opt-in checks of the DSP0236 header rules that decoding doesn't enforce. Decoding only makes
+//! sure a message is long enough; [`StrictValidator`] also rejects messages that break the rules
+//! for the header fields, and tells which completion code a request breaking them gets.
+use thiserror::Error;
+
+use crate::{
+    base::{
+        MctpBaseLibError, MctpBaseLibResult, TransportHeader,
+        MCTP_BASE_PROTOCOL_SUPPORTED_HDR_VERSION,
+    },
+    control::{
+        enums::{CommandCode, CompletionCode, MessageType},
+        models::ControlMsgHeader,
+        ControlMessage, ControlPayload,
+    },
+};
+
+/// Which way a control message travels, as told by its Rq bit
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MessageDirection {
+    Request,
+    Response,
+}
+
+/// A DSP0236 rule broken by a decoded message
+#[derive(Error, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProtocolViolation {
+    #[error("unsupported transport header version ({version})")]
+    HeaderVersion { version: u8 },
+
+    #[error("reserved bits set in the {header} header")]
+    ReservedBits { header: &'static str },
+
+    #[error("message type {msg_type:#04x} isn't a control message")]
+    NotControl { msg_type: u8 },
+
+    #[error("integrity check bit set on a control message")]
+    IntegrityCheck,
+
+    #[error("Rq bit ({rq}) doesn't match the tag owner bit ({tag_owner})")]
+    RequestBitMismatch { rq: u8, tag_owner: u8 },
+
+    #[error("datagram bit set on a response")]
+    DatagramResponse,
+
+    #[error("reserved command code ({command:?})")]
+    ReservedCommand { command: CommandCode },
+
+    #[error("{command:?} {found:?} received where a {expected:?} was expected")]
+    WrongDirection {
+        command: CommandCode,
+        expected: MessageDirection,
+        found: MessageDirection,
+    },
+
+    #[error("message is {found} bytes long instead of {expected}")]
+    Length { expected: usize, found: usize },
+}
+
+impl ProtocolViolation {
+    /// Completion code of the response to a request breaking the rule
+    pub fn completion_code(&self) -> CompletionCode {
+        match self {
+            Self::Length { .. } => CompletionCode::ErrorInvalidLength,
+            _ => CompletionCode::ErrorInvalidData,
+        }
+    }
+}
+
+/// Checks decoded control messages against the DSP0236 header rules.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StrictValidator {
+    direction: Option<MessageDirection>,
+}
+
+impl StrictValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also rejects control messages travelling the other way, e.g. responses received by an
+    /// endpoint that only answers requests.
+    pub fn expecting(direction: MessageDirection) -> Self {
+        Self {
+            direction: Some(direction),
+        }
+    }
+
+    pub fn check_transport_header(&self, hdr: &TransportHeader) -> Result<(), ProtocolViolation> {
+        if hdr.header_version() != MCTP_BASE_PROTOCOL_SUPPORTED_HDR_VERSION {
+            return Err(ProtocolViolation::HeaderVersion {
+                version: hdr.header_version(),
+            });
+        }
+        if hdr.rsvd() != 0 {
+            return Err(ProtocolViolation::ReservedBits {
+                header: "transport",
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks the control header carried with the transport header `hdr`.
+    pub fn check_control_header(
+        &self,
+        hdr: &TransportHeader,
+        control_hdr: &ControlMsgHeader,
+    ) -> Result<(), ProtocolViolation> {
+        if control_hdr.msg_type() != MessageType::Control as u8 {
+            return Err(ProtocolViolation::NotControl {
+                msg_type: control_hdr.msg_type(),
+            });
+        }
+        if control_hdr.integrity_check() != 0 {
+            return Err(ProtocolViolation::IntegrityCheck);
+        }
+        if control_hdr.rsvd() != 0 {
+            return Err(ProtocolViolation::ReservedBits { header: "control" });
+        }
+        // requests are sent by the tag owner, responses go back with the tag owner bit cleared
+        if control_hdr.rq() != hdr.tag_owner() {
+            return Err(ProtocolViolation::RequestBitMismatch {
+                rq: control_hdr.rq(),
+                tag_owner: hdr.tag_owner(),
+            });
+        }
+        let found = match control_hdr.rq() {
+            0 => MessageDirection::Response,
+            _ => MessageDirection::Request,
+        };
+        if found == MessageDirection::Response && control_hdr.d_bit() != 0 {
+            return Err(ProtocolViolation::DatagramResponse);
+        }
+        let command = control_hdr.command_code;
+        if matches!(command, CommandCode::Reserved | CommandCode::Unknown) {
+            return Err(ProtocolViolation::ReservedCommand { command });
+        }
+        match self.direction {
+            Some(expected) if expected != found => Err(ProtocolViolation::WrongDirection {
+                command,
+                expected,
+                found,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks both headers of a control message.
+    pub fn check(&self, payload: &ControlPayload) -> Result<(), ProtocolViolation> {
+        self.check_transport_header(&payload.hdr)?;
+        self.check_control_header(&payload.hdr, &payload.control_hdr)
+    }
+
+    /// Checks the headers and decodes the message, which must be exactly as long as its command
+    /// needs.
+    pub fn decode(&self, payload: &ControlPayload) -> MctpBaseLibResult<ControlMessage> {
+        self.check(payload)?;
+        let msg = ControlMessage::try_from(payload)?;
+        let expected = bytes::Bytes::from(msg.clone()).len();
+        if payload.payload.len() != expected {
+            return Err(ProtocolViolation::Length {
+                expected,
+                found: payload.payload.len(),
+            }
+            .into());
+        }
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::get_eid;
+    use bytes::Bytes;
+
+    fn request() -> ControlPayload {
+        let hdr = TransportHeader::builder()
+            .src_eid(0x08)
+            .dst_eid(0x20)
+            .msg_tag(1)
+            .tag_owner(true)
+            .start_of_msg(true)
+            .end_of_msg(true)
+            .build();
+        let control_hdr = ControlMsgHeader::new(CommandCode::GetEndpointID, 1, false, true, false);
+        ControlPayload::new(hdr, control_hdr, get_eid::Request { hdr: control_hdr })
+    }
+
+    #[test]
+    fn test_valid_request() {
+        let validator = StrictValidator::expecting(MessageDirection::Request);
+        assert!(validator.check(&request()).is_ok());
+        assert!(matches!(
+            validator.decode(&request()),
+            Ok(ControlMessage::Request(_))
+        ));
+    }
+
+    #[test]
+    fn test_header_violations() {
+        let validator = StrictValidator::new();
+
+        let mut payload = request();
+        payload.hdr.set_header_version(2);
+        assert_eq!(
+            validator.check(&payload),
+            Err(ProtocolViolation::HeaderVersion { version: 2 })
+        );
+
+        let mut payload = request();
+        payload.hdr.set_rsvd(1);
+        assert_eq!(
+            validator.check(&payload),
+            Err(ProtocolViolation::ReservedBits {
+                header: "transport"
+            })
+        );
+
+        let mut payload = request();
+        payload.control_hdr.set_integrity_check(1);
+        assert_eq!(
+            validator.check(&payload),
+            Err(ProtocolViolation::IntegrityCheck)
+        );
+
+        let mut payload = request();
+        payload.hdr.set_tag_owner(0);
+        assert_eq!(
+            validator.check(&payload),
+            Err(ProtocolViolation::RequestBitMismatch {
+                rq: 1,
+                tag_owner: 0
+            })
+        );
+
+        let mut payload = request();
+        payload.control_hdr.command_code = CommandCode::Reserved;
+        assert!(matches!(
+            validator.check(&payload),
+            Err(ProtocolViolation::ReservedCommand { .. })
+        ));
+    }
+
+    #[test]
+    fn test_wrong_direction() {
+        let request = request();
+        let resp = get_eid::Response::from(
+            get_eid::Request {
+                hdr: request.control_hdr,
+            },
+            CompletionCode::Success,
+            0x20,
+            get_eid::EidType::Dynamic,
+            get_eid::EndpointType::Simple,
+            0,
+        );
+        let payload = request.create_response_payload(resp.hdr, resp.into());
+
+        assert!(StrictValidator::new().check(&payload).is_ok());
+        let err = StrictValidator::expecting(MessageDirection::Request)
+            .check(&payload)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ProtocolViolation::WrongDirection {
+                command: CommandCode::GetEndpointID,
+                expected: MessageDirection::Request,
+                found: MessageDirection::Response,
+            }
+        );
+        assert_eq!(err.completion_code(), CompletionCode::ErrorInvalidData);
+    }
+
+    #[test]
+    fn test_length_violations() {
+        let validator = StrictValidator::new();
+        let mut bytes = Bytes::from(request()).to_vec();
+        bytes.push(0x00);
+        let payload = ControlPayload::try_from(Bytes::from(bytes)).unwrap();
+
+        // decoding on its own ignores the extra byte
+        assert!(ControlMessage::try_from(&payload).is_ok());
+        let err = validator.decode(&payload).unwrap_err();
+        assert!(matches!(
+            err,
+            MctpBaseLibError::ProtocolViolation(ProtocolViolation::Length {
+                expected: 3,
+                found: 4
+            })
+        ));
+        assert_eq!(err.completion_code(), CompletionCode::ErrorInvalidLength);
+    }
+}
@@ -26,5 +26,8 @@
 //! the Control Message type and any IDs/Codes defined in the base spec.
 #![allow(dead_code, unused)]
 
+// lets the code generated by mctp-emu-derive name this crate the same way from inside and outside
+extern crate self as mctp_base_lib;
+
 pub mod base;
 pub mod control;
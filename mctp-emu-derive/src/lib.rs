@@ -20,10 +20,12 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use proc_macro;
-use proc_macro2;
+use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{
+    parse_macro_input, punctuated::Punctuated, token::Comma, Data, DeriveInput, Field, Fields,
+    Meta, NestedMeta, Type,
+};
 
 #[proc_macro_attribute]
 pub fn add_binary_derives(
@@ -38,57 +40,17 @@ pub fn add_binary_derives(
     output.into()
 }
 
-#[proc_macro_derive(FromBinary)]
+/// Derives the wire format of a fixed size (`#[repr(C, packed)]`) structure. Fields are encoded in
+/// declaration order with their `WireCodec` impl; bitfield storage (`[u8; N]`) is copied as is.
+/// Integer fields can pick their byte order with `#[wire(be)]` or `#[wire(le)]`.
+#[proc_macro_derive(FromBinary, attributes(wire))]
 pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let DeriveInput { ident, .. } = parse_macro_input!(input);
-    let output = quote! {
-        impl From<#ident> for Vec<u8> {
-            fn from(t: #ident) -> Self {
-                bincode::serialize(&t).unwrap()
-            }
-        }
-
-        impl From<#ident> for bytes::Bytes {
-            fn from(t: #ident) -> Self {
-                bytes::Bytes::from(bincode::serialize(&t).unwrap())
-            }
-        }
-
-        impl TryFrom<Vec<u8>> for #ident {
-            type Error = MctpBaseLibError;
-            fn try_from(vec: Vec<u8>) -> std::result::Result<Self, Self::Error> {
-                let struct_size = std::mem::size_of::<#ident>();
-                if vec.len() < struct_size {
-                    return Err(MctpBaseLibError::InvalidPayloadSize {
-                        expected: struct_size.to_string(),
-                        found: vec.len().to_string(),
-                    });
-                }
-                bincode::deserialize(&vec[..])
-                    .context("Failed deserializing message")
-                    .map_err(|err| MctpBaseLibError::Other(err))
-            }
-        }
-
-        impl TryFrom<bytes::Bytes> for #ident {
-            type Error = MctpBaseLibError;
-            fn try_from(bytes: bytes::Bytes) -> std::result::Result<Self, Self::Error> {
-                let struct_size = std::mem::size_of::<#ident>();
-                if bytes.len() < struct_size {
-                    return Err(MctpBaseLibError::InvalidPayloadSize {
-                        expected: struct_size.to_string(),
-                        found: bytes.len().to_string(),
-                    });
-                }
-                bincode::deserialize(bytes.as_ref())
-                    .context("Failed deserializing message")
-                    .map_err(|err| MctpBaseLibError::Other(err))
-            }
-        }
-    };
-    output.into()
+    let input: DeriveInput = parse_macro_input!(input);
+    match fixed_message(input, false) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
-
 #[proc_macro_attribute]
 pub fn add_from_control_payload_derives(
     _metadata: proc_macro::TokenStream,
@@ -106,18 +68,23 @@ pub fn add_from_control_payload_derives(
 pub fn derive_control_msg_response(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let DeriveInput { ident, .. } = parse_macro_input!(input);
     let output = quote! {
-        impl crate::control::ControlMsgReponseStatus for #ident {
-            fn is_success(&self) -> anyhow::Result<()> {
-                if CompletionCode::from(self.completion_code) != CompletionCode::Success {
-                    return Err(Error::msg(format!(
-                        "Response returned an error code: {:?}",
-                        self.completion_code
-                    )));
+        impl mctp_base_lib::control::ControlMsgReponseStatus for #ident {
+            fn is_success(&self) -> std::result::Result<(), mctp_base_lib::base::MctpBaseLibError> {
+                let completion_code = self.response_code();
+                if !completion_code.is_success() {
+                    return Err(mctp_base_lib::base::MctpBaseLibError::ErrorResponse {
+                        command: { self.hdr }.command_code,
+                        completion_code,
+                    });
                 }
                 Ok(())
             }
 
-            fn completion_code(&self) -> CompletionCode {
+            fn completion_code(&self) -> mctp_base_lib::control::enums::CompletionCode {
+                self.completion_code.into()
+            }
+
+            fn response_code(&self) -> mctp_base_lib::control::enums::ResponseCode {
                 self.completion_code.into()
             }
         }
@@ -125,57 +92,539 @@ pub fn derive_control_msg_response(input: proc_macro::TokenStream) -> proc_macro
     output.into()
 }
 
-#[proc_macro_derive(FromControlPayload)]
+/// Same as `FromBinary`, and also decodes the structure from the body of a `ControlPayload`.
+#[proc_macro_derive(FromControlPayload, attributes(wire))]
 pub fn derive_control_payload(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let DeriveInput { ident, .. } = parse_macro_input!(input);
-    let output = quote! {
-        impl From<#ident> for Vec<u8> {
-            fn from(t: #ident) -> Self {
-                bincode::serialize(&t).unwrap()
+    let input: DeriveInput = parse_macro_input!(input);
+    match fixed_message(input, true) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+enum Endian {
+    Big,
+    Little,
+}
+
+/// How a field is laid out on the wire
+#[derive(Default)]
+struct WireFieldAttrs {
+    /// Prefixed by a one byte count of entries (or of bytes for a `Bytes` field)
+    count_prefixed: bool,
+    /// Takes up the rest of the message
+    rest: bool,
+    /// Only present when the message's completion code is `Success`
+    on_success: bool,
+    /// Byte order of an integer field, instead of its `WireCodec` impl
+    endian: Option<Endian>,
+}
+
+/// Collects the flags of every `#[wire(...)]` attribute in `attrs`.
+fn parse_wire_flags(attrs: &[syn::Attribute]) -> syn::Result<Vec<syn::Ident>> {
+    let mut flags = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("wire")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "expected #[wire(...)]")),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) => match path.get_ident() {
+                    Some(ident) => flags.push(ident.clone()),
+                    None => return Err(syn::Error::new_spanned(path, "expected a wire flag")),
+                },
+                other => return Err(syn::Error::new_spanned(other, "expected a wire flag")),
+            }
+        }
+    }
+    Ok(flags)
+}
+
+fn parse_wire_field_attrs(field: &Field) -> syn::Result<WireFieldAttrs> {
+    let mut attrs = WireFieldAttrs::default();
+    for flag in parse_wire_flags(&field.attrs)? {
+        match flag.to_string().as_str() {
+            "count_prefixed" => attrs.count_prefixed = true,
+            "rest" => attrs.rest = true,
+            "on_success" => attrs.on_success = true,
+            "be" => attrs.endian = Some(Endian::Big),
+            "le" => attrs.endian = Some(Endian::Little),
+            _ => return Err(syn::Error::new_spanned(flag, "unknown wire field flag")),
+        }
+    }
+    if attrs.count_prefixed && attrs.rest {
+        return Err(syn::Error::new_spanned(
+            field,
+            "a field can't be both count prefixed and take the rest of the message",
+        ));
+    }
+    if attrs.endian.is_some() && (attrs.count_prefixed || attrs.rest) {
+        return Err(syn::Error::new_spanned(
+            field,
+            "byte order only applies to integer fields",
+        ));
+    }
+    Ok(attrs)
+}
+
+fn named_fields(input: &DeriveInput) -> syn::Result<&Punctuated<Field, Comma>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(&fields.named),
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                "wire formats can only be derived for named fields",
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            "wire formats can only be derived for structs",
+        )),
+    }
+}
+
+/// Whether `ty` is `Bytes` (or `bytes::Bytes`), which is sized in bytes rather than entries
+fn is_bytes(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Bytes"),
+        _ => false,
+    }
+}
+
+/// Returns the expression decoding a field from `buf` and the statements appending `field` (a
+/// reference to the field's value) to `buf`.
+fn field_codec(ty: &Type, attrs: &WireFieldAttrs) -> (TokenStream, TokenStream) {
+    if let Some(endian) = &attrs.endian {
+        let (from_bytes, to_bytes) = match endian {
+            Endian::Big => (quote!(from_be_bytes), quote!(to_be_bytes)),
+            Endian::Little => (quote!(from_le_bytes), quote!(to_le_bytes)),
+        };
+        return (
+            quote! {{
+                let mut raw = [0u8; ::std::mem::size_of::<#ty>()];
+                mctp_base_lib::base::ensure_remaining(buf, raw.len())?;
+                bytes::Buf::copy_to_slice(buf, &mut raw);
+                <#ty>::#from_bytes(raw)
+            }},
+            quote! { bytes::BufMut::put_slice(buf, &field.#to_bytes()); },
+        );
+    }
+
+    if attrs.count_prefixed && is_bytes(ty) {
+        (
+            quote! {{
+                let count = <u8 as mctp_base_lib::base::WireCodec>::decode_from(buf)? as usize;
+                mctp_base_lib::base::ensure_remaining(buf, count)?;
+                buf.split_to(count)
+            }},
+            quote! {
+                let count = mctp_base_lib::base::encode_count(buf, field.len());
+                bytes::BufMut::put_slice(buf, &field[..count]);
+            },
+        )
+    } else if attrs.count_prefixed {
+        (
+            quote! {{
+                let count = <u8 as mctp_base_lib::base::WireCodec>::decode_from(buf)? as usize;
+                mctp_base_lib::base::decode_entries(buf, count)?
+            }},
+            quote! {
+                let count = mctp_base_lib::base::encode_count(buf, field.len());
+                mctp_base_lib::base::encode_entries(buf, &field[..count]);
+            },
+        )
+    } else if attrs.rest && is_bytes(ty) {
+        (
+            quote! { buf.split_to(buf.len()) },
+            quote! { bytes::BufMut::put_slice(buf, &field[..]); },
+        )
+    } else if attrs.rest {
+        (
+            quote! { buf.split_to(buf.len()).to_vec() },
+            quote! { bytes::BufMut::put_slice(buf, &field[..]); },
+        )
+    } else {
+        (
+            quote! { <#ty as mctp_base_lib::base::WireCodec>::decode_from(buf)? },
+            quote! { mctp_base_lib::base::WireCodec::encode_into(field, buf); },
+        )
+    }
+}
+
+/// Conversions shared by every derived wire format, on top of its `WireCodec` impl
+fn conversions(ident: &syn::Ident, capacity: TokenStream, control_payload: bool) -> TokenStream {
+    let control_payload_impl = control_payload.then(|| {
+        quote! {
+            impl TryFrom<mctp_base_lib::control::ControlPayload> for #ident {
+                type Error = mctp_base_lib::base::MctpBaseLibError;
+                fn try_from(
+                    msg: mctp_base_lib::control::ControlPayload,
+                ) -> std::result::Result<Self, Self::Error> {
+                    Self::try_from(msg.payload)
+                }
             }
         }
+    });
 
+    quote! {
         impl From<#ident> for bytes::Bytes {
             fn from(t: #ident) -> Self {
-                let ser = bincode::serialize(&t).unwrap();
-                println!("DEBUG: {:#?}", ser);
-                bytes::Bytes::from(ser)
+                let mut buf = bytes::BytesMut::with_capacity(#capacity);
+                mctp_base_lib::base::WireCodec::encode_into(&t, &mut buf);
+                buf.freeze()
+            }
+        }
+
+        impl From<#ident> for Vec<u8> {
+            fn from(t: #ident) -> Self {
+                bytes::Bytes::from(t).to_vec()
+            }
+        }
+
+        impl TryFrom<Vec<u8>> for #ident {
+            type Error = mctp_base_lib::base::MctpBaseLibError;
+            fn try_from(vec: Vec<u8>) -> std::result::Result<Self, Self::Error> {
+                Self::try_from(bytes::Bytes::from(vec))
+            }
+        }
+
+        #control_payload_impl
+    }
+}
+
+fn fixed_message(input: DeriveInput, control_payload: bool) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let mut names = Vec::new();
+    let mut decoders = Vec::new();
+    let mut encoders = Vec::new();
+    for field in named_fields(&input)? {
+        let attrs = parse_wire_field_attrs(field)?;
+        if attrs.count_prefixed || attrs.rest || attrs.on_success {
+            return Err(syn::Error::new_spanned(
+                field,
+                "fixed size structures can only set the byte order of a field",
+            ));
+        }
+        let name = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let (decode, encode) = field_codec(ty, &attrs);
+        decoders.push(quote! { let #name: #ty = #decode; });
+        // packed fields can't be borrowed in place, so encode from a copy
+        encoders.push(quote! {{
+            let field = &{ self.#name };
+            #encode
+        }});
+        names.push(name);
+    }
+
+    let conversions = conversions(
+        ident,
+        quote!(::std::mem::size_of::<#ident>()),
+        control_payload,
+    );
+    Ok(quote! {
+        impl mctp_base_lib::base::WireCodec for #ident {
+            fn decode_from(buf: &mut bytes::Bytes) -> mctp_base_lib::base::MctpBaseLibResult<Self> {
+                mctp_base_lib::base::ensure_remaining(buf, ::std::mem::size_of::<#ident>())?;
+                #(#decoders)*
+                Ok(Self { #(#names),* })
+            }
+
+            #[allow(unused_variables)]
+            fn encode_into(&self, buf: &mut bytes::BytesMut) {
+                #(#encoders)*
+            }
+        }
+
+        /// Decodes the structure from the front of the buffer, ignoring anything that follows it
+        impl TryFrom<bytes::Bytes> for #ident {
+            type Error = mctp_base_lib::base::MctpBaseLibError;
+            fn try_from(mut bytes: bytes::Bytes) -> std::result::Result<Self, Self::Error> {
+                <Self as mctp_base_lib::base::WireCodec>::decode_from(&mut bytes)
+            }
+        }
+
+        #conversions
+    })
+}
+
+/// Derives the wire format of a message whose size isn't fixed, field by field in declaration
+/// order. Fields are encoded with their `WireCodec` impl unless marked with:
+///
+/// - `#[wire(count_prefixed)]`: a `Vec` of entries (or `Bytes`) preceded by a one byte count.
+///   Constructors of the message should reject longer lists with `check_count`.
+/// - `#[wire(rest)]`: a `Bytes` or `Vec<u8>` holding everything left in the message. Only the
+///   last field may use it.
+/// - `#[wire(on_success)]`: only present when the `completion_code` field is `Success`, and left
+///   at its default value when decoding an error response.
+/// - `#[wire(be)]` / `#[wire(le)]`: an integer sent in the given byte order.
+///
+/// `#[wire(control_payload)]` on the struct adds `TryFrom<ControlPayload>`. Decoding a message
+/// fails when a count doesn't match the bytes that follow it.
+#[proc_macro_derive(WireMessage, attributes(wire))]
+pub fn derive_wire_message(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+    match wire_message(input) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn wire_message(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let mut control_payload = false;
+    for flag in parse_wire_flags(&input.attrs)? {
+        match flag.to_string().as_str() {
+            "control_payload" => control_payload = true,
+            _ => return Err(syn::Error::new_spanned(flag, "unknown wire message flag")),
+        }
+    }
+
+    let fields = named_fields(&input)?;
+    let mut names = Vec::new();
+    let mut decoders = Vec::new();
+    let mut encoders = Vec::new();
+    let mut has_completion_code = false;
+    for (index, field) in fields.iter().enumerate() {
+        let attrs = parse_wire_field_attrs(field)?;
+        let name = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        if attrs.rest && index + 1 != fields.len() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "only the last field can take the rest of the message",
+            ));
+        }
+
+        let (decode, encode) = field_codec(ty, &attrs);
+        let encode = quote! {{
+            let field = &self.#name;
+            #encode
+        }};
+        if attrs.on_success {
+            if !has_completion_code {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "#[wire(on_success)] needs a completion_code field before it",
+                ));
+            }
+            decoders.push(quote! {
+                let #name: #ty = if success { #decode } else { Default::default() };
+            });
+            encoders.push(quote! {
+                if success #encode
+            });
+        } else {
+            decoders.push(quote! { let #name: #ty = #decode; });
+            encoders.push(encode);
+            if name == "completion_code" {
+                has_completion_code = true;
+                decoders.push(quote! {
+                    let success = completion_code
+                        == mctp_base_lib::control::CompletionCode::Success as u8;
+                });
+                encoders.push(quote! {
+                    let success = self.completion_code
+                        == mctp_base_lib::control::CompletionCode::Success as u8;
+                });
+            }
+        }
+        names.push(name);
+    }
+
+    let conversions = conversions(ident, quote!(0), control_payload);
+    Ok(quote! {
+        impl mctp_base_lib::base::WireCodec for #ident {
+            #[allow(unused_variables)]
+            fn decode_from(buf: &mut bytes::Bytes) -> mctp_base_lib::base::MctpBaseLibResult<Self> {
+                #(#decoders)*
+                Ok(Self { #(#names),* })
+            }
+
+            #[allow(unused_variables)]
+            fn encode_into(&self, buf: &mut bytes::BytesMut) {
+                #(#encoders)*
             }
         }
 
+        /// Decodes the whole buffer, which must not hold anything past the end of the message
         impl TryFrom<bytes::Bytes> for #ident {
-            type Error = MctpBaseLibError;
+            type Error = mctp_base_lib::base::MctpBaseLibError;
             fn try_from(bytes: bytes::Bytes) -> std::result::Result<Self, Self::Error> {
-                let struct_size = std::mem::size_of::<#ident>();
-                if bytes.len() < struct_size {
-                    return Err(MctpBaseLibError::InvalidPayloadSize {
-                        expected: struct_size.to_string(),
-                        found: bytes.len().to_string(),
+                let size = bytes.len();
+                let mut buf = bytes;
+                let msg = <Self as mctp_base_lib::base::WireCodec>::decode_from(&mut buf)?;
+                if !buf.is_empty() {
+                    return Err(mctp_base_lib::base::MctpBaseLibError::InvalidPayloadSize {
+                        expected: (size - buf.len()).to_string(),
+                        found: size.to_string(),
                     });
                 }
-                bincode::deserialize(bytes.as_ref())
-                    .context("Failed deserializing message")
-                    .map_err(|err| MctpBaseLibError::Other(err))
+                Ok(msg)
+            }
+        }
+
+        #conversions
+    })
+}
+
+/// A bitfield of a `BitfieldStruct`, read from its `#[bitfield(...)]` attribute
+struct Bitfield {
+    /// Name of the raw accessors generated by `BitfieldStruct`
+    name: syn::Ident,
+    /// Raw integer type of the bitfield
+    ty: Type,
+    /// Name and enum type of the typed accessors, when the bitfield holds an enum
+    typed: Option<(syn::Ident, Type)>,
+}
+
+fn parse_bitfield(attr: &syn::Attribute) -> syn::Result<Bitfield> {
+    let list = match attr.parse_meta()? {
+        Meta::List(list) => list,
+        meta => return Err(syn::Error::new_spanned(meta, "expected #[bitfield(...)]")),
+    };
+    let (mut name, mut ty, mut field, mut enum_ty) = (None, None, None, None);
+    for nested in &list.nested {
+        if let NestedMeta::Meta(Meta::NameValue(pair)) = nested {
+            let value = match &pair.lit {
+                syn::Lit::Str(value) => value,
+                lit => return Err(syn::Error::new_spanned(lit, "expected a string")),
+            };
+            match pair
+                .path
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .as_deref()
+            {
+                Some("name") => name = Some(value.parse::<syn::Ident>()?),
+                Some("ty") => ty = Some(value.parse::<Type>()?),
+                Some("field") => field = Some(value.parse::<syn::Ident>()?),
+                Some("enum_ty") => enum_ty = Some(value.parse::<Type>()?),
+                // the bit range and anything else is only used by BitfieldStruct
+                _ => {}
             }
         }
+    }
+    let (name, ty) = match (name, ty) {
+        (Some(name), Some(ty)) => (name, ty),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "bitfield needs a name and a ty",
+            ))
+        }
+    };
+    let typed = match enum_ty {
+        Some(enum_ty) => {
+            let field = match field {
+                Some(field) => field,
+                None => match name.to_string().strip_prefix("raw_") {
+                    Some(field) => syn::Ident::new(field, name.span()),
+                    None => {
+                        return Err(syn::Error::new_spanned(
+                            attr,
+                            "a bitfield with an enum_ty needs a field name (or a raw_ prefix)",
+                        ))
+                    }
+                },
+            };
+            Some((field, enum_ty))
+        }
+        None => None,
+    };
+    Ok(Bitfield { name, ty, typed })
+}
+
+/// Generates a `Debug` and `Display` for a `BitfieldStruct` that list its logical fields by name,
+/// with each bitfield in place of the byte array storing it. Bitfields holding an enum also get
+/// typed accessors when their attribute names the enum:
+///
+/// `#[bitfield(name = "raw_status", enum_ty = "Status", ty = "uint8_t", bits = "0..=1")]`
+///
+/// adds `status()` and `set_status()` on top of the raw `raw_status()` and `set_raw_status()`.
+/// The typed accessors take their name from `field = "..."` when the raw name has no `raw_`
+/// prefix. The layout of the structure isn't changed.
+#[proc_macro_derive(BitfieldView, attributes(bitfield))]
+pub fn derive_bitfield_view(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+    match bitfield_view(input) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn bitfield_view(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let mut labels = Vec::new();
+    let mut values = Vec::new();
+    let mut accessors = Vec::new();
+    for field in named_fields(&input)? {
+        let name = field.ident.as_ref().unwrap();
+        let bitfields = field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("bitfield"))
+            .map(parse_bitfield)
+            .collect::<syn::Result<Vec<_>>>()?;
+        if bitfields.is_empty() {
+            labels.push(name.to_string());
+            // packed fields can't be borrowed in place, so show a copy
+            values.push(quote! { { self.#name } });
+            continue;
+        }
+
+        for Bitfield { name, ty, typed } in bitfields {
+            match typed {
+                Some((field, enum_ty)) => {
+                    let setter = syn::Ident::new(&format!("set_{}", field), field.span());
+                    let raw_setter = syn::Ident::new(&format!("set_{}", name), name.span());
+                    accessors.push(quote! {
+                        pub fn #field(&self) -> #enum_ty {
+                            #enum_ty::from(self.#name())
+                        }
 
-        impl TryFrom<ControlPayload> for #ident {
-            type Error = MctpBaseLibError;
-            fn try_from(msg: ControlPayload) -> std::result::Result<Self, Self::Error> {
-                let struct_size = std::mem::size_of::<#ident>();
-                if msg.payload.len() < struct_size {
-                    return Err(MctpBaseLibError::InvalidPayloadSize {
-                        expected: struct_size.to_string(),
-                        found: msg.payload.len().to_string(),
+                        pub fn #setter(&mut self, value: #enum_ty) {
+                            self.#raw_setter(value as #ty);
+                        }
                     });
+                    labels.push(field.to_string());
+                    values.push(quote! { self.#field() });
+                }
+                None => {
+                    labels.push(name.to_string());
+                    values.push(quote! { self.#name() });
                 }
-                bincode::deserialize(msg.payload.as_ref())
-                    .context("Failed deserializing message")
-                    .map_err(|err| MctpBaseLibError::Other(err))
             }
         }
-    };
-    output.into()
+    }
+
+    Ok(quote! {
+        impl #ident {
+            #(#accessors)*
+        }
+
+        impl std::fmt::Debug for #ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!(#ident))
+                    #(.field(#labels, &#values))*
+                    .finish()
+            }
+        }
+
+        /// Lists the fields on one line, e.g. `Name field=value other=value`
+        impl std::fmt::Display for #ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(stringify!(#ident))?;
+                #(write!(f, " {}={:?}", #labels, #values)?;)*
+                Ok(())
+            }
+        }
+    })
 }
 
 #[proc_macro_derive(DeserializeU8Enum)]
@@ -0,0 +1,1069 @@
+//! Control message handling for endpoints that manage other endpoints (bus owners and bridges)
+use anyhow::anyhow;
+use bytes::Bytes;
+use std::sync::RwLock;
+use std::time::Duration;
+use tracing::{event, Level};
+use uuid::Uuid;
+
+use mctp_base_lib::base::MctpBaseLibError;
+use mctp_base_lib::control::{
+    enums::{CommandCode, CompletionCode, RoutingEntryType},
+    get_routing_table::{RoutingTableEntry, MAX_PORT_NUMBER},
+    models::ControlMsgHeader,
+    resolve_uuid::UuidResolutionEntry,
+    routing_info_update::RoutingInfoEntry,
+    ControlMsgReponseStatus, *,
+};
+
+use crate::{
+    endpoint::{next_instance_id, read_pages},
+    network::{
+        BindingDescriptor, BindingInfo, MctpNetworkHandle, Neighbour, NeighbourSource, Route,
+        SocketAddress, SocketDescriptor, MCTP_ADDR_BCAST, MCTP_BASELINE_MTU, MCTP_NET_DEFAULT,
+    },
+    MctpEmuResult,
+};
+
+/// Answers the control requests a bus owner or bridge is responsible for, using the routing
+/// information kept by the network it is attached to.
+pub struct DynamicEndpoint {
+    network: MctpNetworkHandle,
+    net: u32,
+    /// UUIDs of the discovered endpoints, in the order they were recorded
+    endpoint_uuids: RwLock<Vec<(u8, Uuid)>>,
+    /// Requests are checked with a [`StrictValidator`] before being handled
+    strict_validation: bool,
+}
+
+impl DynamicEndpoint {
+    pub fn new(network: MctpNetworkHandle) -> Self {
+        Self {
+            network,
+            net: MCTP_NET_DEFAULT,
+            endpoint_uuids: Default::default(),
+            strict_validation: false,
+        }
+    }
+
+    /// Rejects requests breaking the DSP0236 header rules or with the wrong length, instead of
+    /// handling whatever could be decoded from them.
+    pub fn with_strict_validation(mut self) -> Self {
+        self.strict_validation = true;
+        self
+    }
+
+    /// Records the UUID of a discovered endpoint, replacing any UUID recorded for the same EID.
+    pub fn record_endpoint_uuid(&self, eid: u8, uuid: Uuid) {
+        let mut endpoint_uuids = self.endpoint_uuids.write().unwrap();
+        endpoint_uuids.retain(|(existing, _)| *existing != eid);
+        endpoint_uuids.push((eid, uuid));
+    }
+
+    /// Reads the UUID of the endpoint at `addr` and records it, so Resolve UUID requests find the
+    /// endpoint. The endpoint has to have been assigned an EID.
+    pub async fn register_endpoint(
+        &self,
+        sd: SocketDescriptor,
+        addr: SocketAddress,
+    ) -> MctpEmuResult<Uuid> {
+        let eid = match addr {
+            SocketAddress::Basic { address, .. } | SocketAddress::Extended { address, .. } => {
+                address
+            }
+        };
+        if eid == 0 || eid == MCTP_ADDR_BCAST {
+            return Err(anyhow!("endpoint at {:?} has no EID to register", addr).into());
+        }
+        let uuid = get_endpoint_uuid(&self.network, sd, addr).await?;
+        self.record_endpoint_uuid(eid, uuid);
+        Ok(uuid)
+    }
+
+    /// Runs [`discover_endpoints`] and registers every endpoint that answered from an EID it
+    /// already has. Endpoints that answered from the null EID are returned as well, and can be
+    /// registered with [`DynamicEndpoint::register_endpoint`] once they are assigned an EID.
+    pub async fn discover_endpoints(
+        &self,
+        sd: SocketDescriptor,
+        binding: SocketAddress,
+        timeout: Duration,
+    ) -> MctpEmuResult<Vec<SocketAddress>> {
+        let found = discover_endpoints(&self.network, sd, binding, timeout).await?;
+        for addr in found.iter().copied() {
+            let (SocketAddress::Basic { address, .. } | SocketAddress::Extended { address, .. }) =
+                addr;
+            if address == 0 {
+                continue;
+            }
+            if let Err(err) = self.register_endpoint(sd, addr).await {
+                event!(Level::WARN, "failed registering {:?}: {:?}", addr, err);
+            }
+        }
+        Ok(found)
+    }
+
+    /// Handles a single control request (starting with the MCTP transport header) received from
+    /// `addr` and returns the encoded response.
+    pub fn handle_request(&self, addr: SocketAddress, bytes: Bytes) -> MctpEmuResult<Bytes> {
+        let payload = ControlPayload::try_from(bytes)?;
+        let msg = if self.strict_validation {
+            StrictValidator::expecting(MessageDirection::Request).decode(&payload)
+        } else {
+            ControlMessage::try_from(&payload)
+        };
+        let req = match msg {
+            Ok(ControlMessage::Request(req)) => req,
+            Ok(ControlMessage::Response(_)) | Err(MctpBaseLibError::UnsupportedCommand { .. }) => {
+                return self.handle_unsupported(&payload)
+            }
+            Err(err) if self.strict_validation => return self.reject(&payload, err),
+            Err(err) => return Err(err.into()),
+        };
+        match req {
+            ControlRequest::ResolveEndpointID(req) => self.handle_resolve_eid(&payload, req),
+            ControlRequest::RoutingInformationUpdate(req) => {
+                self.handle_routing_info_update(addr, &payload, req)
+            }
+            ControlRequest::GetRoutingTableEntries(req) => {
+                self.handle_get_routing_table(&payload, req)
+            }
+            ControlRequest::GetNetworkID(req) => self.handle_get_network_id(&payload, req),
+            ControlRequest::QueryHop(req) => self.handle_query_hop(addr, &payload, req),
+            ControlRequest::ResolveUUID(req) => self.handle_resolve_uuid(&payload, req),
+            _ => self.handle_unsupported(&payload),
+        }
+    }
+
+    /// Answers a request that failed strict validation with the completion code matching `err`.
+    /// Messages that aren't requests can't be answered and are returned as errors.
+    fn reject(&self, payload: &ControlPayload, err: MctpBaseLibError) -> MctpEmuResult<Bytes> {
+        event!(Level::INFO, "rejecting invalid message: {}", err);
+        match payload.create_rejection(&err) {
+            Some(resp_payload) => Ok(Bytes::from(resp_payload)),
+            None => Err(err.into()),
+        }
+    }
+
+    fn handle_unsupported(&self, payload: &ControlPayload) -> MctpEmuResult<Bytes> {
+        event!(
+            Level::INFO,
+            "unsupported command: {:?}",
+            payload.control_hdr
+        );
+        Ok(Bytes::from(payload.create_unsupported_response()))
+    }
+
+    fn handle_resolve_eid(
+        &self,
+        payload: &ControlPayload,
+        req: resolve_eid::Request,
+    ) -> MctpEmuResult<Bytes> {
+        use resolve_eid::*;
+
+        let resp = match self.network.resolve(self.net, req.target_eid) {
+            Some((_, neighbour)) => Response::from(
+                req,
+                CompletionCode::Success,
+                neighbour.eid(),
+                Bytes::copy_from_slice(neighbour.hw_addr()),
+            ),
+            None => Response::from(req, CompletionCode::ErrorInvalidData, 0, Bytes::new()),
+        };
+        event!(Level::INFO, "ResolveEndpointID response: {:?}", resp);
+        let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+        Ok(Bytes::from(resp_payload))
+    }
+
+    fn handle_routing_info_update(
+        &self,
+        addr: SocketAddress,
+        payload: &ControlPayload,
+        req: routing_info_update::Request,
+    ) -> MctpEmuResult<Bytes> {
+        use routing_info_update::*;
+
+        let completion_code = match self.binding_of(addr) {
+            Some(binding_id) => {
+                let mut completion_code = CompletionCode::Success;
+                for entry in req.entries.iter() {
+                    if !self.apply_routing_entry(binding_id, entry) {
+                        completion_code = CompletionCode::ErrorInvalidData;
+                    }
+                }
+                completion_code
+            }
+            None => CompletionCode::ErrorInvalidData,
+        };
+        let resp = Response::from(EmptyRequest { hdr: req.hdr }, completion_code);
+        event!(Level::INFO, "RoutingInformationUpdate response: {:?}", resp);
+        let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+        Ok(Bytes::from(resp_payload))
+    }
+
+    fn handle_get_routing_table(
+        &self,
+        payload: &ControlPayload,
+        req: get_routing_table::Request,
+    ) -> MctpEmuResult<Bytes> {
+        use get_routing_table::*;
+
+        let table = self.routing_table()?;
+        let resp = Response::from_table(req, &table, MCTP_BASELINE_MTU as usize);
+        event!(Level::INFO, "GetRoutingTableEntries response: {:?}", resp);
+        let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+        Ok(Bytes::from(resp_payload))
+    }
+
+    fn handle_get_network_id(
+        &self,
+        payload: &ControlPayload,
+        req: get_network_id::Request,
+    ) -> MctpEmuResult<Bytes> {
+        use get_network_id::*;
+
+        let resp = Response::from(req, CompletionCode::Success, self.network.network_id());
+        event!(Level::INFO, "GetNetworkID response: {:?}", resp);
+        let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+        Ok(Bytes::from(resp_payload))
+    }
+
+    fn handle_query_hop(
+        &self,
+        addr: SocketAddress,
+        payload: &ControlPayload,
+        req: query_hop::Request,
+    ) -> MctpEmuResult<Bytes> {
+        use query_hop::*;
+
+        let resp = match self.network.route_lookup(self.net, req.target_eid) {
+            Some(route) => {
+                let link_mtu = |binding_id| {
+                    self.network
+                        .binding_info(binding_id)
+                        .unwrap_or_default()
+                        .mtu
+                };
+                let incoming = self.binding_of(addr).map_or(MCTP_BASELINE_MTU, link_mtu);
+                let outgoing = route.mtu().min(link_mtu(route.binding_id()));
+                Response::from(
+                    req,
+                    CompletionCode::Success,
+                    route.gateway_eid().unwrap_or(NO_NEXT_BRIDGE),
+                    incoming.min(u16::MAX as u32) as u16,
+                    outgoing.min(u16::MAX as u32) as u16,
+                )
+            }
+            None => Response::from(req, CompletionCode::ErrorInvalidData, NO_NEXT_BRIDGE, 0, 0),
+        };
+        event!(Level::INFO, "QueryHop response: {:?}", resp);
+        let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+        Ok(Bytes::from(resp_payload))
+    }
+
+    fn handle_resolve_uuid(
+        &self,
+        payload: &ControlPayload,
+        req: resolve_uuid::Request,
+    ) -> MctpEmuResult<Bytes> {
+        use resolve_uuid::*;
+
+        let resolved: Vec<_> = self
+            .endpoint_uuids
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, uuid)| *uuid == req.uuid())
+            .filter_map(|(eid, _)| {
+                let (_, neighbour) = self.network.resolve(self.net, *eid)?;
+                Some(UuidResolutionEntry::new(
+                    *eid,
+                    Bytes::copy_from_slice(neighbour.hw_addr()),
+                ))
+            })
+            .collect::<Result<_, _>>()?;
+        let resp = Response::from_entries(req, &resolved, MCTP_BASELINE_MTU as usize);
+        event!(Level::INFO, "ResolveUUID response: {:?}", resp);
+        let resp_payload = payload.create_response_payload(resp.hdr, resp.into());
+        Ok(Bytes::from(resp_payload))
+    }
+
+    /// Describes every route of the network as a routing table entry. Routes without a gateway
+    /// that other routes go through are reported as bridges. Routes out of a binding whose ID
+    /// doesn't fit the port number field are left out.
+    fn routing_table(&self) -> MctpEmuResult<Vec<RoutingTableEntry>> {
+        let routes = self.network.routes(self.net);
+        let entries = routes
+            .iter()
+            .filter_map(|route| {
+                let port_number = match u8::try_from(route.binding_id()) {
+                    Ok(port_number) if port_number <= MAX_PORT_NUMBER => port_number,
+                    _ => {
+                        event!(
+                            Level::WARN,
+                            "Binding {} can't be reported as a port number, leaving out {:?}",
+                            route.binding_id(),
+                            route
+                        );
+                        return None;
+                    }
+                };
+                let entry_type = match route.gateway_eid() {
+                    Some(gateway_eid) if gateway_eid == route.min_eid() => {
+                        RoutingEntryType::BridgeAndDownstreamEids
+                    }
+                    Some(_) => RoutingEntryType::AdditionalBridgeEids,
+                    None if routes
+                        .iter()
+                        .any(|other| other.gateway_eid() == Some(route.min_eid())) =>
+                    {
+                        RoutingEntryType::SingleBridge
+                    }
+                    None => RoutingEntryType::SingleEndpoint,
+                };
+                let next_hop = route.gateway_eid().unwrap_or(route.min_eid());
+                let neighbour = self.network.neighbour_lookup(self.net, next_hop);
+                let binding_info = self
+                    .network
+                    .binding_info(route.binding_id())
+                    .unwrap_or_default();
+                Some(RoutingTableEntry::new(
+                    route.eid_range_size(),
+                    route.min_eid(),
+                    entry_type,
+                    neighbour
+                        .as_ref()
+                        .is_some_and(|n| *n.source() == NeighbourSource::Static),
+                    port_number,
+                    binding_info.transport_binding,
+                    binding_info.physical_medium,
+                    neighbour
+                        .as_ref()
+                        .map(|n| Bytes::copy_from_slice(n.hw_addr()))
+                        .unwrap_or_default(),
+                ))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(entries)
+    }
+
+    /// Binding the request from `addr` arrived on, which is also where its routes point to
+    fn binding_of(&self, addr: SocketAddress) -> Option<BindingDescriptor> {
+        match addr {
+            SocketAddress::Extended { binding_id, .. } => Some(binding_id),
+            SocketAddress::Basic { address, .. } => self
+                .network
+                .resolve(self.net, address)
+                .map(|(_, neighbour)| neighbour.binding_id()),
+        }
+    }
+
+    /// Adds the neighbour and route described by `entry`. Returns `false` when the entry refers to
+    /// a bridge that isn't known yet.
+    fn apply_routing_entry(&self, binding_id: BindingDescriptor, entry: &RoutingInfoEntry) -> bool {
+        let first_eid = entry.starting_eid;
+        let last_eid = entry.last_eid();
+        let gateway_eid = match entry.entry_type {
+            RoutingEntryType::SingleEndpoint | RoutingEntryType::SingleBridge => {
+                self.add_neighbour(binding_id, first_eid, entry);
+                self.network.add_route(Route::new(
+                    first_eid,
+                    first_eid,
+                    self.net,
+                    MCTP_BASELINE_MTU,
+                    binding_id,
+                    None,
+                ));
+                return true;
+            }
+            RoutingEntryType::BridgeAndDownstreamEids => {
+                self.add_neighbour(binding_id, first_eid, entry);
+                first_eid
+            }
+            RoutingEntryType::AdditionalBridgeEids => match self
+                .network
+                .neighbour_lookup_by_hw_addr(self.net, binding_id, &entry.physical_address)
+            {
+                Some(bridge) => bridge.eid(),
+                None => return false,
+            },
+        };
+        self.network.add_route(Route::new(
+            first_eid,
+            last_eid,
+            self.net,
+            MCTP_BASELINE_MTU,
+            binding_id,
+            Some(gateway_eid),
+        ));
+        true
+    }
+
+    fn add_neighbour(&self, binding_id: BindingDescriptor, eid: u8, entry: &RoutingInfoEntry) {
+        self.network.add_neighbour(Neighbour::new(
+            eid,
+            self.net,
+            binding_id,
+            NeighbourSource::Static,
+            &entry.physical_address,
+        ));
+    }
+}
+
+/// Pushes routing information to a bridge, e.g. after the bus owner allocated it an EID pool.
+pub async fn send_routing_information_update(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    bridge: SocketAddress,
+    entries: Vec<RoutingInfoEntry>,
+) -> MctpEmuResult<()> {
+    use routing_info_update::*;
+
+    let ctrl_hdr = ControlMsgHeader::new(
+        CommandCode::RoutingInformationUpdate,
+        next_instance_id(),
+        false,
+        true,
+        false,
+    );
+    let req = Request::new(ctrl_hdr, entries)?;
+    let (_, buf) = network.sendto(sd, Bytes::from(req), bridge).await?;
+
+    let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+    resp.is_success()?;
+    Ok(())
+}
+
+/// Reads the whole routing table of a bridge, following the entry handles across responses.
+pub async fn get_routing_table(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    bridge: SocketAddress,
+) -> MctpEmuResult<Vec<RoutingTableEntry>> {
+    use get_routing_table::*;
+
+    read_pages("routing table", |entry_handle| async move {
+        let ctrl_hdr = ControlMsgHeader::new(
+            CommandCode::GetRoutingTableEntries,
+            next_instance_id(),
+            false,
+            true,
+            false,
+        );
+        let req = Request::new(ctrl_hdr, entry_handle);
+        let (_, buf) = network.sendto(sd, Bytes::from(req), bridge).await?;
+
+        let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+        resp.is_success()?;
+        Ok((resp.next_entry_handle, resp.entries))
+    })
+    .await
+}
+
+/// Reads the UUID of the endpoint at `addr`, e.g. to record it with
+/// [`DynamicEndpoint::record_endpoint_uuid`].
+pub async fn get_endpoint_uuid(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    addr: SocketAddress,
+) -> MctpEmuResult<Uuid> {
+    use get_uuid::*;
+
+    let ctrl_hdr = ControlMsgHeader::new(
+        CommandCode::GetEndpointUUID,
+        next_instance_id(),
+        false,
+        true,
+        false,
+    );
+    let req = Request { hdr: ctrl_hdr };
+    let (_, buf) = network.sendto(sd, Bytes::from(req), addr).await?;
+
+    let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+    resp.is_success()?;
+    Ok(resp.uuid())
+}
+
+/// Asks the endpoint at `addr` which network it is attached to.
+pub async fn get_network_id(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    addr: SocketAddress,
+) -> MctpEmuResult<Uuid> {
+    use get_network_id::*;
+
+    let ctrl_hdr = ControlMsgHeader::new(
+        CommandCode::GetNetworkID,
+        next_instance_id(),
+        false,
+        true,
+        false,
+    );
+    let req = Request { hdr: ctrl_hdr };
+    let (_, buf) = network.sendto(sd, Bytes::from(req), addr).await?;
+
+    let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+    resp.is_success()?;
+    Ok(resp.network_id())
+}
+
+/// Asks a bridge how it forwards messages of `message_type` to `target_eid`.
+pub async fn query_hop(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    bridge: SocketAddress,
+    target_eid: u8,
+    message_type: u8,
+) -> MctpEmuResult<query_hop::Response> {
+    use query_hop::*;
+
+    let ctrl_hdr = ControlMsgHeader::new(
+        CommandCode::QueryHop,
+        next_instance_id(),
+        false,
+        true,
+        false,
+    );
+    let req = Request::new(ctrl_hdr, target_eid, message_type);
+    let (_, buf) = network.sendto(sd, Bytes::from(req), bridge).await?;
+
+    let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+    resp.is_success()?;
+    Ok(resp)
+}
+
+/// Broadcasts a request without additional fields and returns the address of every endpoint that
+/// answered it successfully.
+async fn broadcast_request(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    binding: SocketAddress,
+    command_code: CommandCode,
+    timeout: Duration,
+) -> MctpEmuResult<Vec<SocketAddress>> {
+    let ctrl_hdr = ControlMsgHeader::new(command_code, next_instance_id(), false, true, false);
+    let req = EmptyRequest { hdr: ctrl_hdr };
+    let responses = network
+        .broadcast(sd, Bytes::from(req), binding, timeout)
+        .await?;
+
+    let mut endpoints = Vec::new();
+    for (addr, buf) in responses {
+        match ControlPayload::try_to_response::<EmptyResponse>(buf) {
+            Ok((_, resp)) if resp.is_success().is_ok() => endpoints.push(addr),
+            Ok((_, resp)) => event!(
+                Level::WARN,
+                "{:?} failed from {:?}: {:?}",
+                command_code,
+                addr,
+                resp
+            ),
+            Err(err) => event!(
+                Level::WARN,
+                "bad {:?} response from {:?}: {:?}",
+                command_code,
+                addr,
+                err
+            ),
+        }
+    }
+    Ok(endpoints)
+}
+
+/// Runs the broadcast discovery sequence out of the binding named by `binding`: Prepare for
+/// Endpoint Discovery, then Endpoint Discovery. Returns the address of every endpoint that
+/// answered Endpoint Discovery. Assigning them an EID (or sending Set Endpoint ID with
+/// [`set_eid::Operation::SetDiscoveredFlag`]) sets their discovered flag, so repeating
+/// [`endpoint_discovery`] afterwards only finds endpoints that were missed.
+pub async fn discover_endpoints(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    binding: SocketAddress,
+    timeout: Duration,
+) -> MctpEmuResult<Vec<SocketAddress>> {
+    let prepared = broadcast_request(
+        network,
+        sd,
+        binding,
+        CommandCode::PrepareForEndpointDiscovery,
+        timeout,
+    )
+    .await?;
+    event!(
+        Level::INFO,
+        "{} endpoints prepared for discovery",
+        prepared.len()
+    );
+
+    endpoint_discovery(network, sd, binding, timeout).await
+}
+
+/// Broadcasts Endpoint Discovery and returns the address of every endpoint that answered it.
+pub async fn endpoint_discovery(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    binding: SocketAddress,
+    timeout: Duration,
+) -> MctpEmuResult<Vec<SocketAddress>> {
+    broadcast_request(
+        network,
+        sd,
+        binding,
+        CommandCode::EndpointDiscovery,
+        timeout,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::simple::EndpointDiscoveryState;
+    use crate::network::{loopback::LoopbackBinding, simple_network::SimpleNetwork, *};
+    use crate::phys::smbus_types::SmbusPhysTransportHeader;
+    use anyhow::Result;
+    use bytes::{BufMut, BytesMut};
+    use mctp_base_lib::base::TransportHeader;
+    use mctp_base_lib::control::models::ControlMsgHeader;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio::sync::mpsc::Sender;
+
+    /// Emulates a bus of endpoints that answer the discovery broadcasts transmitted on the
+    /// network. `assigned` holds the EID and UUID of the endpoints that were assigned one, by
+    /// SMBus address. The others answer from the null EID.
+    async fn new_discovery_network(
+        endpoints: Arc<Vec<(u8, EndpointDiscoveryState)>>,
+        assigned: HashMap<u8, (u8, Uuid)>,
+    ) -> Result<Arc<dyn MctpNetwork>> {
+        let (binding, mut transmitted) = LoopbackBinding::new();
+        let binding = Arc::new(tokio::sync::Mutex::new(binding));
+        let network = SimpleNetwork::new_mctp_network(binding.clone())?;
+        network.add_physical_binding(binding.clone()).await?;
+        let rx_callback = binding.lock().await.rx_callback();
+        tokio::spawn(async move {
+            while let Some(buf) = transmitted.recv().await {
+                answer_discovery(&endpoints, &assigned, &rx_callback, buf).unwrap();
+            }
+        });
+        Ok(network)
+    }
+
+    fn answer_discovery(
+        endpoints: &[(u8, EndpointDiscoveryState)],
+        assigned: &HashMap<u8, (u8, Uuid)>,
+        rx_callback: &Sender<NetworkBindingCallbackMsg>,
+        buf: Bytes,
+    ) -> Result<()> {
+        let payload = ControlPayload::try_from(buf)?;
+        for (smbus_addr, state) in endpoints.iter() {
+            let resp_payload = match payload.command_code() {
+                Ok(CommandCode::PrepareForEndpointDiscovery) => {
+                    let resp = state.handle_prepare_for_discovery(1, payload.clone())?;
+                    Some(payload.create_response_payload(resp.hdr, resp.into()))
+                }
+                Ok(CommandCode::EndpointDiscovery) => state
+                    .handle_endpoint_discovery(1, payload.clone())?
+                    .map(|resp| payload.create_response_payload(resp.hdr, resp.into())),
+                Ok(CommandCode::GetEndpointUUID) => match assigned.get(smbus_addr) {
+                    Some((eid, uuid)) if *eid == payload.hdr.destination_eid => {
+                        let req = get_uuid::Request::try_from(payload.clone())?;
+                        let resp = get_uuid::Response::from(req, CompletionCode::Success, *uuid);
+                        Some(payload.create_response_payload(resp.hdr, resp.into()))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            };
+            let Some(mut resp_payload) = resp_payload else {
+                continue;
+            };
+            resp_payload.hdr.source_eid = assigned.get(smbus_addr).map_or(0, |(eid, _)| *eid);
+            let resp = Bytes::from(resp_payload);
+
+            let mut msg = BytesMut::new();
+            msg.put(Bytes::from(SmbusPhysTransportHeader::new(
+                0x10,
+                *smbus_addr,
+                resp.len() as u8,
+            )));
+            msg.put(resp);
+            rx_callback.try_send(NetworkBindingCallbackMsg::Receive {
+                id: 1,
+                buf: msg.freeze(),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn bridge_addr() -> SocketAddress {
+        SocketAddress::Extended {
+            address: 0x30,
+            network: MCTP_NET_DEFAULT,
+            binding_id: 1,
+            phy_addr: 0x30,
+        }
+    }
+
+    fn transport_header() -> TransportHeader {
+        TransportHeader::builder()
+            .src_eid(0x30)
+            .dst_eid(0x08)
+            .msg_tag(1)
+            .tag_owner(true)
+            .start_of_msg(true)
+            .end_of_msg(true)
+            .build()
+    }
+
+    fn resolve_request(target_eid: u8) -> Bytes {
+        let ctrl_hdr = ControlMsgHeader::new(CommandCode::ResolveEndpointID, 1, false, true, false);
+        let req = resolve_eid::Request::new(ctrl_hdr, target_eid);
+        Bytes::from(ControlPayload::new(transport_header(), ctrl_hdr, req))
+    }
+
+    fn get_routing_table_request(entry_handle: u8) -> Bytes {
+        let ctrl_hdr =
+            ControlMsgHeader::new(CommandCode::GetRoutingTableEntries, 1, false, true, false);
+        let req = get_routing_table::Request::new(ctrl_hdr, entry_handle);
+        Bytes::from(ControlPayload::new(transport_header(), ctrl_hdr, req))
+    }
+
+    fn get_network_id_request() -> Bytes {
+        let ctrl_hdr = ControlMsgHeader::new(CommandCode::GetNetworkID, 1, false, true, false);
+        let req = get_network_id::Request { hdr: ctrl_hdr };
+        Bytes::from(ControlPayload::new(transport_header(), ctrl_hdr, req))
+    }
+
+    fn query_hop_request(target_eid: u8) -> Bytes {
+        let ctrl_hdr = ControlMsgHeader::new(CommandCode::QueryHop, 1, false, true, false);
+        let req = query_hop::Request::new(ctrl_hdr, target_eid, 0x01);
+        Bytes::from(ControlPayload::new(transport_header(), ctrl_hdr, req))
+    }
+
+    fn resolve_uuid_request(uuid: Uuid, entry_handle: u8) -> Bytes {
+        let ctrl_hdr = ControlMsgHeader::new(CommandCode::ResolveUUID, 1, false, true, false);
+        let req = resolve_uuid::Request::new(ctrl_hdr, uuid, entry_handle);
+        Bytes::from(ControlPayload::new(transport_header(), ctrl_hdr, req))
+    }
+
+    fn routing_info_update_request(entries: Vec<RoutingInfoEntry>) -> Result<Bytes> {
+        let ctrl_hdr =
+            ControlMsgHeader::new(CommandCode::RoutingInformationUpdate, 1, false, true, false);
+        let req = routing_info_update::Request::new(ctrl_hdr, entries)?;
+        Ok(Bytes::from(ControlPayload::new(
+            transport_header(),
+            ctrl_hdr,
+            req,
+        )))
+    }
+
+    #[tokio::test]
+    async fn test_resolve_eid_through_bridge() -> Result<()> {
+        let network = SimpleNetwork::new_mctp_network(Arc::new(tokio::sync::Mutex::new(
+            LoopbackBinding::default(),
+        )))?;
+        network.add_route(Route::new(0x20, 0x2f, MCTP_NET_DEFAULT, 64, 1, Some(0x10)));
+        network.add_neighbour(Neighbour::new(
+            0x10,
+            MCTP_NET_DEFAULT,
+            1,
+            NeighbourSource::Static,
+            &[0x32],
+        ));
+        let endpoint = DynamicEndpoint::new(network);
+
+        let bytes = endpoint.handle_request(bridge_addr(), resolve_request(0x24))?;
+        let (_, resp) = ControlPayload::try_to_response::<resolve_eid::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        assert_eq!(resp.bridge_eid, 0x10);
+        assert_eq!(resp.physical_address.as_ref(), &[0x32]);
+
+        let bytes = endpoint.handle_request(bridge_addr(), resolve_request(0x40))?;
+        let (_, resp) = ControlPayload::try_to_response::<resolve_eid::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidData);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_network_id() -> Result<()> {
+        let network_id = Uuid::new_v4();
+        let network = SimpleNetwork::new_mctp_network_with_id(
+            Arc::new(tokio::sync::Mutex::new(LoopbackBinding::default())),
+            network_id,
+        )?;
+        let other_network = SimpleNetwork::new_mctp_network(Arc::new(tokio::sync::Mutex::new(
+            LoopbackBinding::default(),
+        )))?;
+        assert_ne!(other_network.network_id(), network_id);
+
+        let endpoint = DynamicEndpoint::new(network);
+        let bytes = endpoint.handle_request(bridge_addr(), get_network_id_request())?;
+        let (_, resp) = ControlPayload::try_to_response::<get_network_id::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        assert_eq!(resp.network_id(), network_id);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_hop() -> Result<()> {
+        let network = SimpleNetwork::new_mctp_network(Arc::new(tokio::sync::Mutex::new(
+            LoopbackBinding::default(),
+        )))?;
+        // a segment behind a bridge, whose MTU below the baseline is raised to it
+        network.add_route(Route::new(0x10, 0x10, MCTP_NET_DEFAULT, 64, 1, None));
+        network.add_route(Route::new(0x20, 0x2f, MCTP_NET_DEFAULT, 32, 1, Some(0x10)));
+        let endpoint = DynamicEndpoint::new(network);
+
+        let bytes = endpoint.handle_request(bridge_addr(), query_hop_request(0x24))?;
+        let (_, resp) = ControlPayload::try_to_response::<query_hop::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        assert_eq!(resp.next_bridge_eid, 0x10);
+        assert_eq!(resp.message_type, 0x01);
+        assert_eq!(resp.max_incoming_unit(), 64);
+        assert_eq!(resp.max_outgoing_unit(), 64);
+
+        let bytes = endpoint.handle_request(bridge_addr(), query_hop_request(0x10))?;
+        let (_, resp) = ControlPayload::try_to_response::<query_hop::Response>(bytes)?;
+        assert_eq!(resp.next_bridge_eid, query_hop::NO_NEXT_BRIDGE);
+
+        let bytes = endpoint.handle_request(bridge_addr(), query_hop_request(0x40))?;
+        let (_, resp) = ControlPayload::try_to_response::<query_hop::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidData);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resolve_uuid() -> Result<()> {
+        let network = SimpleNetwork::new_mctp_network(Arc::new(tokio::sync::Mutex::new(
+            LoopbackBinding::default(),
+        )))?;
+        for eid in [0x20, 0x21, 0x22] {
+            network.add_neighbour(Neighbour::new(
+                eid,
+                MCTP_NET_DEFAULT,
+                1,
+                NeighbourSource::Discover,
+                &[eid + 0x10],
+            ));
+            network.add_route(Route::new(eid, eid, MCTP_NET_DEFAULT, 64, 1, None));
+        }
+        let endpoint = DynamicEndpoint::new(network);
+        let uuid = Uuid::new_v4();
+        endpoint.record_endpoint_uuid(0x20, uuid);
+        endpoint.record_endpoint_uuid(0x21, Uuid::new_v4());
+        endpoint.record_endpoint_uuid(0x22, uuid);
+
+        let bytes = endpoint.handle_request(bridge_addr(), resolve_uuid_request(uuid, 0))?;
+        let (_, resp) = ControlPayload::try_to_response::<resolve_uuid::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        assert_eq!(resp.next_entry_handle, resolve_uuid::NO_MORE_ENTRIES);
+        assert_eq!(
+            resp.entries,
+            vec![
+                UuidResolutionEntry::new(0x20, vec![0x30])?,
+                UuidResolutionEntry::new(0x22, vec![0x32])?,
+            ]
+        );
+
+        let bytes =
+            endpoint.handle_request(bridge_addr(), resolve_uuid_request(Uuid::new_v4(), 0))?;
+        let (_, resp) = ControlPayload::try_to_response::<resolve_uuid::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidData);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_routing_info_update() -> Result<()> {
+        let network = SimpleNetwork::new_mctp_network(Arc::new(tokio::sync::Mutex::new(
+            LoopbackBinding::default(),
+        )))?;
+        let endpoint = DynamicEndpoint::new(network.clone());
+
+        let entries = vec![
+            RoutingInfoEntry::new(RoutingEntryType::SingleEndpoint, 0x09, 1, vec![0x31])?,
+            RoutingInfoEntry::new(RoutingEntryType::SingleBridge, 0x0a, 1, vec![0x32])?,
+            RoutingInfoEntry::new(RoutingEntryType::AdditionalBridgeEids, 0x20, 8, vec![0x32])?,
+        ];
+        let bytes =
+            endpoint.handle_request(bridge_addr(), routing_info_update_request(entries)?)?;
+        let (_, resp) = ControlPayload::try_to_response::<routing_info_update::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+
+        let (route, neighbour) = network.resolve(MCTP_NET_DEFAULT, 0x27).unwrap();
+        assert_eq!(route.gateway_eid(), Some(0x0a));
+        assert_eq!(neighbour.hw_addr(), &[0x32]);
+        assert_eq!(
+            network.resolve(MCTP_NET_DEFAULT, 0x09).unwrap().1.hw_addr(),
+            &[0x31]
+        );
+
+        // Downstream EIDs of a bridge that hasn't been announced are rejected
+        let entries = vec![RoutingInfoEntry::new(
+            RoutingEntryType::AdditionalBridgeEids,
+            0x40,
+            8,
+            vec![0x33],
+        )?];
+        let bytes =
+            endpoint.handle_request(bridge_addr(), routing_info_update_request(entries)?)?;
+        let (_, resp) = ControlPayload::try_to_response::<routing_info_update::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidData);
+        assert!(network.route_lookup(MCTP_NET_DEFAULT, 0x40).is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_routing_table() -> Result<()> {
+        use get_routing_table::*;
+
+        let network = SimpleNetwork::new_mctp_network(Arc::new(tokio::sync::Mutex::new(
+            LoopbackBinding::default(),
+        )))?;
+        let endpoint = DynamicEndpoint::new(network.clone());
+        network.add_neighbour(Neighbour::new(
+            0x10,
+            MCTP_NET_DEFAULT,
+            1,
+            NeighbourSource::Static,
+            &[0x32],
+        ));
+        network.add_route(Route::new(0x10, 0x10, MCTP_NET_DEFAULT, 64, 1, None));
+        network.add_route(Route::new(0x20, 0x27, MCTP_NET_DEFAULT, 64, 1, Some(0x10)));
+        for eid in 0x30..0x38 {
+            network.add_neighbour(Neighbour::new(
+                eid,
+                MCTP_NET_DEFAULT,
+                1,
+                NeighbourSource::Discover,
+                &[eid],
+            ));
+            network.add_route(Route::new(eid, eid, MCTP_NET_DEFAULT, 64, 1, None));
+        }
+
+        let mut table = Vec::new();
+        let mut entry_handle = 0;
+        while entry_handle != NO_MORE_ENTRIES {
+            let bytes =
+                endpoint.handle_request(bridge_addr(), get_routing_table_request(entry_handle))?;
+            let (_, resp) = ControlPayload::try_to_response::<Response>(bytes)?;
+            assert_eq!(resp.response_code(), CompletionCode::Success);
+            assert!(resp.entries.len() < 10);
+            table.extend(resp.entries);
+            entry_handle = resp.next_entry_handle;
+        }
+
+        assert_eq!(table.len(), 10);
+        assert_eq!(table[0].entry_type, RoutingEntryType::SingleBridge);
+        assert!(table[0].static_entry);
+        assert_eq!(table[1].entry_type, RoutingEntryType::AdditionalBridgeEids);
+        assert_eq!(table[1].eid_range_size, 8);
+        assert_eq!(table[1].physical_address.as_ref(), &[0x32]);
+        assert_eq!(table[9].entry_type, RoutingEntryType::SingleEndpoint);
+        assert_eq!(table[9].physical_address.as_ref(), &[0x37]);
+
+        let bytes = endpoint.handle_request(bridge_addr(), get_routing_table_request(10))?;
+        let (_, resp) = ControlPayload::try_to_response::<Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidData);
+
+        // a route covering every EID doesn't overflow the range size
+        network.add_route(Route::new(0x00, 0xff, MCTP_NET_DEFAULT, 64, 1, Some(0x10)));
+        let bytes = endpoint.handle_request(bridge_addr(), get_routing_table_request(10))?;
+        let (_, resp) = ControlPayload::try_to_response::<Response>(bytes)?;
+        assert_eq!(resp.entries[0].eid_range_size, 0xff);
+
+        // binding 0x20 doesn't fit the 5 bit port number, so its route isn't listed
+        network.add_route(Route::new(0x40, 0x40, MCTP_NET_DEFAULT, 64, 0x20, None));
+        let bytes = endpoint.handle_request(bridge_addr(), get_routing_table_request(10))?;
+        let (_, resp) = ControlPayload::try_to_response::<Response>(bytes)?;
+        assert_eq!(resp.entries.len(), 1);
+        assert_eq!(resp.next_entry_handle, NO_MORE_ENTRIES);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_discover_endpoints() -> Result<()> {
+        let discovered = EndpointDiscoveryState::new();
+        discovered.set_discovered(1);
+        let endpoints = Arc::new(vec![
+            (0x20, EndpointDiscoveryState::new()),
+            (0x21, discovered),
+            (0x22, EndpointDiscoveryState::new()),
+        ]);
+        let network = new_discovery_network(endpoints.clone(), HashMap::new()).await?;
+        let sd = network.socket();
+        network.bind(sd, 0x08, 0, 1)?;
+        let bus = SocketAddress::Extended {
+            address: MCTP_ADDR_BCAST,
+            network: MCTP_NET_DEFAULT,
+            binding_id: 1,
+            phy_addr: 0,
+        };
+        let timeout = Duration::from_millis(50);
+
+        // Prepare clears the flag of the endpoint that was already discovered
+        let found = discover_endpoints(&network, sd, bus, timeout).await?;
+        let mut found: Vec<_> = found
+            .iter()
+            .map(|addr| match addr {
+                SocketAddress::Extended { phy_addr, .. } => *phy_addr,
+                SocketAddress::Basic { .. } => unreachable!(),
+            })
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![0x20, 0x21, 0x22]);
+
+        endpoints[1].1.set_discovered(1);
+        let found = endpoint_discovery(&network, sd, bus, timeout).await?;
+        assert_eq!(found.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_discovery_registers_endpoint_uuids() -> Result<()> {
+        let uuid = Uuid::new_v4();
+        let endpoints = Arc::new(vec![
+            (0x20, EndpointDiscoveryState::new()),
+            (0x21, EndpointDiscoveryState::new()),
+        ]);
+        let assigned = HashMap::from([(0x21, (0x30, uuid))]);
+        let network = new_discovery_network(endpoints, assigned).await?;
+        let sd = network.socket();
+        network.bind(sd, 0x08, 0, 1)?;
+        let bus = SocketAddress::Extended {
+            address: MCTP_ADDR_BCAST,
+            network: MCTP_NET_DEFAULT,
+            binding_id: 1,
+            phy_addr: 0,
+        };
+        let endpoint = DynamicEndpoint::new(network);
+
+        // only the endpoint that already has an EID can be registered
+        let found = endpoint
+            .discover_endpoints(sd, bus, Duration::from_millis(50))
+            .await?;
+        assert_eq!(found.len(), 2);
+        let unassigned = found
+            .iter()
+            .find(|addr| matches!(addr, SocketAddress::Extended { address: 0, .. }))
+            .unwrap();
+        assert!(endpoint.register_endpoint(sd, *unassigned).await.is_err());
+
+        let bytes = endpoint.handle_request(bridge_addr(), resolve_uuid_request(uuid, 0))?;
+        let (_, resp) = ControlPayload::try_to_response::<resolve_uuid::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        assert_eq!(
+            resp.entries,
+            vec![UuidResolutionEntry::new(0x30, vec![0x21])?]
+        );
+
+        Ok(())
+    }
+}
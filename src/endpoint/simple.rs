@@ -0,0 +1,1148 @@
+//! Control message handling for simple endpoints
+use anyhow::anyhow;
+use bytes::{Bytes, BytesMut};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, RwLock};
+use tracing::{event, Level};
+use uuid::Uuid;
+
+use mctp_base_lib::base::MctpBaseLibError;
+use mctp_base_lib::control::{
+    enums::{CommandCode, CompletionCode, MessageType},
+    get_eid::{EidType, EndpointType},
+    get_version_support::{VersionEntry, MCTP_BASE_SPEC_MSG_TYPE, MCTP_BASE_SPEC_VERSION},
+    models::{ControlMsgHeader, RateLimit},
+    query_supported_interfaces::SupportedInterface,
+    resolve_uuid::UuidResolutionEntry,
+    ControlMsgReponseStatus, *,
+};
+
+use crate::{
+    endpoint::{next_instance_id, read_pages},
+    network::{
+        BindingDescriptor, MctpNetworkHandle, Neighbour, NeighbourSource, Route, SocketAddress,
+        SocketDescriptor, MCTP_ADDR_BCAST, MCTP_BASELINE_MTU, MCTP_NET_DEFAULT,
+    },
+    MctpEmuEmptyResult, MctpEmuResult,
+};
+
+/// Replaces the default handling of a control command by a [`SimpleEndpoint`]. Returns the encoded
+/// response, or `None` when the endpoint stays silent.
+pub type ControlRequestHandler = Arc<
+    dyn Fn(
+            &SimpleEndpoint,
+            SocketAddress,
+            &ControlPayload,
+            ControlRequest,
+        ) -> MctpEmuResult<Option<Bytes>>
+        + Send
+        + Sync,
+>;
+
+/// Commands a [`SimpleEndpoint`] answers without a registered handler
+const SUPPORTED_COMMANDS: &[CommandCode] = &[
+    CommandCode::SetEndpointID,
+    CommandCode::GetEndpointID,
+    CommandCode::GetEndpointUUID,
+    CommandCode::GetMCTPVersionSupport,
+    CommandCode::GetMessageTypeSupport,
+    CommandCode::GetNetworkID,
+    CommandCode::PrepareForEndpointDiscovery,
+    CommandCode::EndpointDiscovery,
+    CommandCode::QuerySupportedInterfaces,
+    CommandCode::QueryRateLimit,
+    CommandCode::RequestTXRateLimit,
+    CommandCode::UpdateRateLimit,
+];
+
+/// Static properties of a [`SimpleEndpoint`]
+#[derive(Clone, Debug)]
+pub struct SimpleEndpointConfig {
+    pub uuid: Uuid,
+    /// EID the endpoint starts with and returns to on Reset EID, `None` when the endpoint only gets
+    /// its EID from the bus owner
+    pub static_eid: Option<u8>,
+    /// Message types reported by Get Message Type Support
+    pub message_types: Vec<u8>,
+    /// Versions reported by Get MCTP Version Support, per message type
+    pub versions: HashMap<u8, Vec<VersionEntry>>,
+    /// Capabilities reported by Query Rate Limit, which bound the limits peers can request
+    pub rate_limit: RateLimitCapabilities,
+    /// Rejects requests breaking the DSP0236 header rules or with the wrong length, see
+    /// [`StrictValidator`]
+    pub strict_validation: bool,
+}
+
+impl Default for SimpleEndpointConfig {
+    fn default() -> Self {
+        Self {
+            uuid: Uuid::new_v4(),
+            static_eid: None,
+            message_types: Vec::new(),
+            versions: HashMap::from([
+                (MCTP_BASE_SPEC_MSG_TYPE, vec![MCTP_BASE_SPEC_VERSION]),
+                (MessageType::Control as u8, vec![MCTP_BASE_SPEC_VERSION]),
+            ]),
+            rate_limit: RateLimitCapabilities::default(),
+            strict_validation: false,
+        }
+    }
+}
+
+/// An endpoint attached to a network that answers the control commands every MCTP endpoint has to
+/// support. Other commands get `ErrorUnsupportedCmd` unless a handler was registered for them with
+/// [`SimpleEndpoint::set_handler`], which also replaces the built-in handling.
+pub struct SimpleEndpoint {
+    network: MctpNetworkHandle,
+    sd: SocketDescriptor,
+    config: SimpleEndpointConfig,
+    eid: AtomicU8,
+    discovery: EndpointDiscoveryState,
+    rate_limit: EndpointRateLimitState,
+    handlers: RwLock<BTreeMap<CommandCode, ControlRequestHandler>>,
+}
+
+impl SimpleEndpoint {
+    /// Opens a control message socket on `network` for a new endpoint
+    pub fn attach(
+        network: MctpNetworkHandle,
+        config: SimpleEndpointConfig,
+    ) -> MctpEmuResult<Arc<Self>> {
+        let eid = config.static_eid.unwrap_or(0);
+        let sd = network.socket();
+        network.bind(sd, eid, MessageType::Control as u8, 0)?;
+        let rate_limit = EndpointRateLimitState::new(network.clone(), config.rate_limit);
+        Ok(Arc::new(Self {
+            network,
+            sd,
+            config,
+            eid: AtomicU8::new(eid),
+            discovery: EndpointDiscoveryState::new(),
+            rate_limit,
+            handlers: Default::default(),
+        }))
+    }
+
+    /// EID presently assigned to the endpoint, `0` when it has none
+    pub fn eid(&self) -> u8 {
+        self.eid.load(Ordering::SeqCst)
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.config.uuid
+    }
+
+    pub fn network(&self) -> &MctpNetworkHandle {
+        &self.network
+    }
+
+    /// Socket the endpoint receives requests on, which can also be used to send its own requests
+    pub fn socket(&self) -> SocketDescriptor {
+        self.sd
+    }
+
+    pub fn discovery(&self) -> &EndpointDiscoveryState {
+        &self.discovery
+    }
+
+    pub fn rate_limit(&self) -> &EndpointRateLimitState {
+        &self.rate_limit
+    }
+
+    /// Handles `command_code` with `handler` instead of the built-in handling.
+    pub fn set_handler(&self, command_code: CommandCode, handler: ControlRequestHandler) {
+        self.handlers.write().unwrap().insert(command_code, handler);
+    }
+
+    /// Answers the requests delivered to the endpoint's socket until the socket is closed.
+    pub async fn run(self: Arc<Self>) -> MctpEmuEmptyResult {
+        loop {
+            let (addr, buf) = self.network.recvfrom(self.sd).await?;
+            match self.handle_request(addr, buf) {
+                Ok(Some(resp)) => {
+                    if let Err(err) = self.network.send_message(resp, addr).await {
+                        tracing::warn!("failed sending response: {:?}", err);
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => tracing::warn!("failed handling request: {:?}", err),
+            }
+        }
+    }
+
+    /// Handles a single control message (starting with the MCTP transport header) received from
+    /// `addr` and returns the encoded response, or `None` when there is nothing to answer.
+    pub fn handle_request(
+        &self,
+        addr: SocketAddress,
+        bytes: Bytes,
+    ) -> MctpEmuResult<Option<Bytes>> {
+        let payload = ControlPayload::try_from(bytes)?;
+        let validator = self
+            .config
+            .strict_validation
+            .then(|| StrictValidator::expecting(MessageDirection::Request));
+        if let Some(Err(violation)) = validator.map(|validator| validator.check(&payload)) {
+            return Ok(self.reject(&payload, violation.into()));
+        }
+        if payload.control_hdr.rq() == 0 {
+            event!(Level::INFO, "ignoring unexpected response: {:?}", payload);
+            return Ok(None);
+        }
+
+        let command_code = payload.control_hdr.command_code;
+        let handler = self.handlers.read().unwrap().get(&command_code).cloned();
+        if handler.is_none() && !SUPPORTED_COMMANDS.contains(&command_code) {
+            return Ok(Some(self.handle_unsupported(&payload)));
+        }
+        let msg = match validator {
+            Some(validator) => validator.decode(&payload),
+            None => ControlMessage::try_from(&payload),
+        };
+        let req = match msg {
+            Ok(ControlMessage::Request(req)) => req,
+            Ok(ControlMessage::Response(_)) => return Ok(None),
+            Err(err) if validator.is_some() => return Ok(self.reject(&payload, err)),
+            Err(err) => return Err(err.into()),
+        };
+        if let Some(handler) = handler {
+            return handler(self, addr, &payload, req);
+        }
+
+        match req {
+            ControlRequest::SetEndpointID(req) => self.handle_set_eid(addr, &payload, req),
+            ControlRequest::GetEndpointID(req) => self.handle_get_eid(&payload, req),
+            ControlRequest::GetEndpointUUID(req) => self.handle_get_uuid(&payload, req),
+            ControlRequest::GetMCTPVersionSupport(req) => {
+                self.handle_get_version_support(&payload, req)
+            }
+            ControlRequest::GetMessageTypeSupport(req) => {
+                self.handle_get_message_type_support(&payload, req)
+            }
+            ControlRequest::GetNetworkID(req) => self.handle_get_network_id(&payload, req),
+            ControlRequest::PrepareForEndpointDiscovery(_) => {
+                let binding_id = self.binding_of(addr)?;
+                let resp = self
+                    .discovery
+                    .handle_prepare_for_discovery(binding_id, payload.clone())?;
+                Ok(Some(self.respond(&payload, resp.hdr, resp)))
+            }
+            ControlRequest::EndpointDiscovery(_) => {
+                let binding_id = self.binding_of(addr)?;
+                let resp = self
+                    .discovery
+                    .handle_endpoint_discovery(binding_id, payload.clone())?;
+                Ok(resp.map(|resp| self.respond(&payload, resp.hdr, resp)))
+            }
+            ControlRequest::QuerySupportedInterfaces(_) => {
+                let resp =
+                    handle_query_supported_interfaces(&self.network, self.eid(), payload.clone())?;
+                Ok(Some(self.respond(&payload, resp.hdr, resp)))
+            }
+            ControlRequest::QueryRateLimit(_) => {
+                let resp = self.rate_limit.handle_query_rate_limit(payload.clone())?;
+                Ok(Some(self.respond(&payload, resp.hdr, resp)))
+            }
+            ControlRequest::RequestTXRateLimit(_) => {
+                let resp = self
+                    .rate_limit
+                    .handle_request_tx_rate_limit(payload.clone())?;
+                Ok(Some(self.respond(&payload, resp.hdr, resp)))
+            }
+            ControlRequest::UpdateRateLimit(_) => {
+                let resp = self.rate_limit.handle_update_rate_limit(payload.clone())?;
+                Ok(Some(self.respond(&payload, resp.hdr, resp)))
+            }
+            _ => Ok(Some(self.handle_unsupported(&payload))),
+        }
+    }
+
+    /// Encodes a response to the request in `payload`, sent from the endpoint's present EID.
+    pub fn respond<T: Into<Bytes>>(
+        &self,
+        payload: &ControlPayload,
+        control_hdr: ControlMsgHeader,
+        body: T,
+    ) -> Bytes {
+        self.send_from_eid(payload.create_response_payload(control_hdr, body.into()))
+    }
+
+    fn send_from_eid(&self, mut resp_payload: ControlPayload) -> Bytes {
+        resp_payload.hdr.source_eid = self.eid();
+        Bytes::from(resp_payload)
+    }
+
+    /// Answers a request that failed strict validation with the completion code matching `err`.
+    /// Messages that aren't requests are dropped.
+    fn reject(&self, payload: &ControlPayload, err: MctpBaseLibError) -> Option<Bytes> {
+        event!(Level::INFO, "rejecting invalid message: {}", err);
+        let resp_payload = payload.create_rejection(&err)?;
+        Some(self.send_from_eid(resp_payload))
+    }
+
+    fn binding_of(&self, addr: SocketAddress) -> MctpEmuResult<BindingDescriptor> {
+        match addr {
+            SocketAddress::Extended { binding_id, .. } => Ok(binding_id),
+            SocketAddress::Basic { address, .. } => self
+                .network
+                .neighbour_lookup(MCTP_NET_DEFAULT, address)
+                .map(|neighbour| neighbour.binding_id())
+                .ok_or_else(|| anyhow!("request from EID {address:#04x} has no binding").into()),
+        }
+    }
+
+    fn assign_eid(&self, eid: u8) -> MctpEmuResult<()> {
+        self.network.set_address(self.sd, eid)?;
+        self.eid.store(eid, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn handle_unsupported(&self, payload: &ControlPayload) -> Bytes {
+        event!(
+            Level::INFO,
+            "unsupported command: {:?}",
+            payload.control_hdr
+        );
+        self.send_from_eid(payload.create_unsupported_response())
+    }
+
+    fn handle_set_eid(
+        &self,
+        addr: SocketAddress,
+        payload: &ControlPayload,
+        req: set_eid::Request,
+    ) -> MctpEmuResult<Option<Bytes>> {
+        use set_eid::*;
+
+        let binding_id = self.binding_of(addr)?;
+        let completion_code = match req.operation() {
+            Operation::SetEid | Operation::ForceEid => {
+                if req.eid == 0 || req.eid == MCTP_ADDR_BCAST {
+                    CompletionCode::ErrorInvalidData
+                } else {
+                    self.assign_eid(req.eid)?;
+                    CompletionCode::Success
+                }
+            }
+            Operation::ResetEid => match self.config.static_eid {
+                Some(eid) => {
+                    self.assign_eid(eid)?;
+                    CompletionCode::Success
+                }
+                None => CompletionCode::ErrorInvalidData,
+            },
+            Operation::SetDiscoveredFlag => CompletionCode::Success,
+        };
+        let assignment_status = if completion_code == CompletionCode::Success {
+            self.discovery.set_discovered(binding_id);
+            EidAssignmentStatus::Accepted
+        } else {
+            EidAssignmentStatus::Rejected
+        };
+        let resp = Response::from(
+            req,
+            completion_code,
+            EidAllocationStatus::NoPoolSupport,
+            assignment_status,
+            self.eid(),
+            0,
+        );
+        event!(Level::INFO, "SetEndpointID response: {:?}", resp);
+        Ok(Some(self.respond(payload, resp.hdr, resp)))
+    }
+
+    fn handle_get_eid(
+        &self,
+        payload: &ControlPayload,
+        req: get_eid::Request,
+    ) -> MctpEmuResult<Option<Bytes>> {
+        use get_eid::*;
+
+        let eid = self.eid();
+        let eid_type = match self.config.static_eid {
+            None => EidType::Dynamic,
+            Some(static_eid) if static_eid == eid => EidType::StaticMatch,
+            Some(_) => EidType::StaticMismatch,
+        };
+        let resp = Response::from(
+            req,
+            CompletionCode::Success,
+            eid,
+            eid_type,
+            EndpointType::Simple,
+            0,
+        );
+        event!(Level::INFO, "GetEndpointID response: {:?}", resp);
+        Ok(Some(self.respond(payload, resp.hdr, resp)))
+    }
+
+    fn handle_get_uuid(
+        &self,
+        payload: &ControlPayload,
+        req: get_uuid::Request,
+    ) -> MctpEmuResult<Option<Bytes>> {
+        use get_uuid::*;
+
+        let resp = Response::from(req, CompletionCode::Success, self.config.uuid);
+        event!(Level::INFO, "GetEndpointUUID response: {:?}", resp);
+        Ok(Some(self.respond(payload, resp.hdr, resp)))
+    }
+
+    fn handle_get_version_support(
+        &self,
+        payload: &ControlPayload,
+        req: get_version_support::Request,
+    ) -> MctpEmuResult<Option<Bytes>> {
+        use get_version_support::*;
+
+        let resp = match self.config.versions.get(&req.message_type_number) {
+            Some(entries) => Response::from(req, CompletionCode::Success, entries.clone())?,
+            None => Response::message_type_not_supported(req),
+        };
+        event!(Level::INFO, "GetMCTPVersionSupport response: {:?}", resp);
+        Ok(Some(self.respond(payload, resp.hdr, resp)))
+    }
+
+    fn handle_get_message_type_support(
+        &self,
+        payload: &ControlPayload,
+        req: get_message_type_support::Request,
+    ) -> MctpEmuResult<Option<Bytes>> {
+        use get_message_type_support::*;
+
+        let resp = Response::from(
+            req,
+            CompletionCode::Success,
+            self.config.message_types.clone(),
+        )?;
+        event!(Level::INFO, "GetMessageTypeSupport response: {:?}", resp);
+        Ok(Some(self.respond(payload, resp.hdr, resp)))
+    }
+
+    fn handle_get_network_id(
+        &self,
+        payload: &ControlPayload,
+        req: get_network_id::Request,
+    ) -> MctpEmuResult<Option<Bytes>> {
+        use get_network_id::*;
+
+        let resp = Response::from(req, CompletionCode::Success, self.network.network_id());
+        event!(Level::INFO, "GetNetworkID response: {:?}", resp);
+        Ok(Some(self.respond(payload, resp.hdr, resp)))
+    }
+}
+
+/// Per-binding "discovered" flag of an endpoint. The flag starts out clear, is cleared by Prepare
+/// for Endpoint Discovery and set by Set Endpoint ID, and while it is set the endpoint stays silent
+/// on Endpoint Discovery.
+#[derive(Debug, Default)]
+pub struct EndpointDiscoveryState {
+    discovered: RwLock<HashSet<BindingDescriptor>>,
+}
+
+impl EndpointDiscoveryState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_discovered(&self, binding_id: BindingDescriptor) -> bool {
+        self.discovered.read().unwrap().contains(&binding_id)
+    }
+
+    pub fn set_discovered(&self, binding_id: BindingDescriptor) {
+        self.discovered.write().unwrap().insert(binding_id);
+    }
+
+    pub fn clear_discovered(&self, binding_id: BindingDescriptor) {
+        self.discovered.write().unwrap().remove(&binding_id);
+    }
+
+    /// Clears the flag of the binding the request arrived on and acknowledges it. The caller sends
+    /// the response from its own EID.
+    pub fn handle_prepare_for_discovery(
+        &self,
+        binding_id: BindingDescriptor,
+        payload: ControlPayload,
+    ) -> MctpEmuResult<prepare_endpoint_discovery::Response> {
+        use prepare_endpoint_discovery::*;
+
+        let req = Request::try_from(payload.clone())?;
+        self.clear_discovered(binding_id);
+        let resp = Response::from(req, CompletionCode::Success);
+        event!(
+            Level::INFO,
+            "PrepareForEndpointDiscovery response: {:?}",
+            resp
+        );
+        Ok(resp)
+    }
+
+    /// Answers Endpoint Discovery, or returns `None` when the endpoint was already discovered on
+    /// the binding the request arrived on.
+    pub fn handle_endpoint_discovery(
+        &self,
+        binding_id: BindingDescriptor,
+        payload: ControlPayload,
+    ) -> MctpEmuResult<Option<endpoint_discovery::Response>> {
+        use endpoint_discovery::*;
+
+        let req = Request::try_from(payload.clone())?;
+        if self.is_discovered(binding_id) {
+            event!(
+                Level::INFO,
+                "already discovered, ignoring EndpointDiscovery"
+            );
+            return Ok(None);
+        }
+        let resp = Response::from(req, CompletionCode::Success);
+        event!(Level::INFO, "EndpointDiscovery response: {:?}", resp);
+        Ok(Some(resp))
+    }
+}
+
+/// Rate limiting capabilities of an endpoint, reported through Query Rate Limit
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct RateLimitCapabilities {
+    /// Size of the receive buffer, in bytes
+    pub receive_buffer_size: u32,
+    /// Highest rate the endpoint can receive at, in packets per second
+    pub max_receive_rate: u32,
+    /// Largest burst and fastest rate the endpoint's transmitter can be limited to
+    pub max_tx_limit: RateLimit,
+    /// Slowest rate the endpoint's transmitter can be limited to, in packets per second
+    pub min_tx_rate: u32,
+}
+
+/// Answers the rate limiting commands of an endpoint. The limits requested by a peer are applied
+/// to the network the endpoint is attached to, which throttles everything sent to that peer. The
+/// caller sends the responses from its own EID.
+pub struct EndpointRateLimitState {
+    network: MctpNetworkHandle,
+    capabilities: RateLimitCapabilities,
+}
+
+impl EndpointRateLimitState {
+    pub fn new(network: MctpNetworkHandle, capabilities: RateLimitCapabilities) -> Self {
+        Self {
+            network,
+            capabilities,
+        }
+    }
+
+    pub fn capabilities(&self) -> RateLimitCapabilities {
+        self.capabilities
+    }
+
+    /// Brings `limit` into the range supported by the endpoint's transmitter
+    pub fn clamp(&self, limit: RateLimit) -> RateLimit {
+        let max = self.capabilities.max_tx_limit;
+        let mut burst_size = limit.burst_size.max(1);
+        if max.burst_size != 0 {
+            burst_size = burst_size.min(max.burst_size);
+        }
+        let mut max_rate = limit.max_rate.max(self.capabilities.min_tx_rate);
+        if max.is_limited() {
+            max_rate = max_rate.min(max.max_rate);
+        }
+        RateLimit::new(burst_size, max_rate)
+    }
+
+    fn present_limit(&self, eid: u8) -> RateLimit {
+        self.network
+            .tx_rate_limit(MCTP_NET_DEFAULT, eid)
+            .unwrap_or_default()
+    }
+
+    /// Reports the capabilities and the limit applied to the requester.
+    pub fn handle_query_rate_limit(
+        &self,
+        payload: ControlPayload,
+    ) -> MctpEmuResult<query_rate_limit::Response> {
+        use query_rate_limit::*;
+
+        let req = Request::try_from(payload.clone())?;
+        let caps = self.capabilities;
+        let resp = Response::from(
+            req,
+            CompletionCode::Success,
+            caps.receive_buffer_size,
+            caps.max_receive_rate,
+            caps.max_tx_limit,
+            caps.min_tx_rate,
+            self.present_limit(payload.hdr.source_eid),
+        );
+        event!(Level::INFO, "QueryRateLimit response: {:?}", resp);
+        Ok(resp)
+    }
+
+    /// Limits what is sent to the requester, clamped to the supported range. An unlimited rate
+    /// removes the limit.
+    pub fn handle_request_tx_rate_limit(
+        &self,
+        payload: ControlPayload,
+    ) -> MctpEmuResult<request_tx_rate_limit::Response> {
+        use request_tx_rate_limit::*;
+
+        let req = Request::try_from(payload.clone())?;
+        let eid = payload.hdr.source_eid;
+        let applied = if req.tx_limit.is_limited() {
+            self.clamp(req.tx_limit)
+        } else {
+            RateLimit::default()
+        };
+        self.network
+            .set_tx_rate_limit(MCTP_NET_DEFAULT, eid, Some(applied));
+        let resp = Response::from(req, CompletionCode::Success, applied);
+        event!(Level::INFO, "RequestTXRateLimit response: {:?}", resp);
+        Ok(resp)
+    }
+
+    /// Keeps the rate used towards the requester within the receive rate it announced.
+    pub fn handle_update_rate_limit(
+        &self,
+        payload: ControlPayload,
+    ) -> MctpEmuResult<update_rate_limit::Response> {
+        use update_rate_limit::*;
+
+        let req = Request::try_from(payload.clone())?;
+        let eid = payload.hdr.source_eid;
+        let mut hdr = req.hdr;
+        hdr.set_rq(0);
+        let resp = if req.max_receive_rate == 0 {
+            Response::new(hdr, CompletionCode::ErrorInvalidData as u8)
+        } else {
+            let present = self.present_limit(eid);
+            let burst_size = if present.is_limited() {
+                present.burst_size
+            } else {
+                self.capabilities.max_tx_limit.burst_size
+            };
+            let max_rate = if present.is_limited() {
+                present.max_rate.min(req.max_receive_rate)
+            } else {
+                req.max_receive_rate
+            };
+            let applied = self.clamp(RateLimit::new(burst_size, max_rate));
+            self.network
+                .set_tx_rate_limit(MCTP_NET_DEFAULT, eid, Some(applied));
+            Response::new(hdr, CompletionCode::Success as u8)
+        };
+        event!(Level::INFO, "UpdateRateLimit response: {:?}", resp);
+        Ok(resp)
+    }
+}
+
+/// Interfaces of an endpoint reachable at `eid` on every binding attached to `network`
+pub fn supported_interfaces(network: &MctpNetworkHandle, eid: u8) -> Vec<SupportedInterface> {
+    network
+        .bindings()
+        .into_iter()
+        .map(|(_, info)| SupportedInterface::new(info.transport_binding, info.physical_medium, eid))
+        .collect()
+}
+
+/// Lists the interfaces of an endpoint reachable at `eid` on every binding of `network`.
+pub fn handle_query_supported_interfaces(
+    network: &MctpNetworkHandle,
+    eid: u8,
+    payload: ControlPayload,
+) -> MctpEmuResult<query_supported_interfaces::Response> {
+    use query_supported_interfaces::*;
+
+    let req = Request::try_from(payload.clone())?;
+    let resp = Response::from(
+        req,
+        CompletionCode::Success,
+        supported_interfaces(network, eid),
+    )?;
+    event!(Level::INFO, "QuerySupportedInterfaces response: {:?}", resp);
+    Ok(resp)
+}
+
+/// Asks the bus owner to resolve `target_eid` and records the answer in the network's neighbour
+/// and route tables, so the target can then be reached with a [`SocketAddress::Basic`] address.
+pub async fn resolve_endpoint_id(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    bus_owner: SocketAddress,
+    target_eid: u8,
+) -> MctpEmuResult<resolve_eid::Response> {
+    use resolve_eid::*;
+
+    let ctrl_hdr = ControlMsgHeader::new(
+        CommandCode::ResolveEndpointID,
+        next_instance_id(),
+        false,
+        true,
+        false,
+    );
+    let req = Request::new(ctrl_hdr, target_eid);
+    let (addr, buf) = network.sendto(sd, Bytes::from(req), bus_owner).await?;
+
+    let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+    resp.is_success()?;
+
+    let (net, binding_id) = match addr {
+        SocketAddress::Extended {
+            network,
+            binding_id,
+            ..
+        } => (network, binding_id),
+        SocketAddress::Basic { .. } => {
+            return Err(anyhow!("response is missing the binding it was received on").into())
+        }
+    };
+    if network.neighbour_lookup(net, resp.bridge_eid).is_none() {
+        network.add_neighbour(Neighbour::new(
+            resp.bridge_eid,
+            net,
+            binding_id,
+            NeighbourSource::Discover,
+            resp.physical_address.as_ref(),
+        ));
+    }
+    if network.route_lookup(net, target_eid).is_none() {
+        let gateway_eid = (resp.bridge_eid != target_eid).then_some(resp.bridge_eid);
+        network.add_route(Route::new(
+            target_eid,
+            target_eid,
+            net,
+            MCTP_BASELINE_MTU,
+            binding_id,
+            gateway_eid,
+        ));
+    }
+
+    Ok(resp)
+}
+
+/// Asks the bus owner for every EID registered with `uuid`, following the entry handles across
+/// responses.
+pub async fn resolve_uuid(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    bus_owner: SocketAddress,
+    uuid: Uuid,
+) -> MctpEmuResult<Vec<UuidResolutionEntry>> {
+    use resolve_uuid::*;
+
+    read_pages("UUID resolution", |entry_handle| async move {
+        let ctrl_hdr = ControlMsgHeader::new(
+            CommandCode::ResolveUUID,
+            next_instance_id(),
+            false,
+            true,
+            false,
+        );
+        let req = Request::new(ctrl_hdr, uuid, entry_handle);
+        let (_, buf) = network.sendto(sd, Bytes::from(req), bus_owner).await?;
+
+        let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+        resp.is_success()?;
+        Ok((resp.next_entry_handle, resp.entries))
+    })
+    .await
+}
+
+/// Asks `addr` for its rate limiting capabilities and the limit it applies to this endpoint.
+pub async fn query_rate_limit(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    addr: SocketAddress,
+) -> MctpEmuResult<query_rate_limit::Response> {
+    use query_rate_limit::*;
+
+    let ctrl_hdr = ControlMsgHeader::new(
+        CommandCode::QueryRateLimit,
+        next_instance_id(),
+        false,
+        true,
+        false,
+    );
+    let req = Request { hdr: ctrl_hdr };
+    let (_, buf) = network.sendto(sd, Bytes::from(req), addr).await?;
+
+    let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+    resp.is_success()?;
+    Ok(resp)
+}
+
+/// Asks `addr` to limit what it transmits to this endpoint and returns the limit it applied.
+pub async fn request_tx_rate_limit(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    addr: SocketAddress,
+    tx_limit: RateLimit,
+) -> MctpEmuResult<RateLimit> {
+    use request_tx_rate_limit::*;
+
+    let ctrl_hdr = ControlMsgHeader::new(
+        CommandCode::RequestTXRateLimit,
+        next_instance_id(),
+        false,
+        true,
+        false,
+    );
+    let req = Request::new(ctrl_hdr, tx_limit);
+    let (_, buf) = network.sendto(sd, Bytes::from(req), addr).await?;
+
+    let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+    resp.is_success()?;
+    Ok(resp.present_tx_limit)
+}
+
+/// Tells `addr` the rate this endpoint can now receive at.
+pub async fn update_rate_limit(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    addr: SocketAddress,
+    max_receive_rate: u32,
+) -> MctpEmuResult<()> {
+    use update_rate_limit::*;
+
+    let ctrl_hdr = ControlMsgHeader::new(
+        CommandCode::UpdateRateLimit,
+        next_instance_id(),
+        false,
+        true,
+        false,
+    );
+    let req = Request::new(ctrl_hdr, max_receive_rate);
+    let (_, buf) = network.sendto(sd, Bytes::from(req), addr).await?;
+
+    let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+    resp.is_success()?;
+    Ok(())
+}
+
+/// Asks `addr` for the physical interfaces it is attached to.
+pub async fn query_supported_interfaces(
+    network: &MctpNetworkHandle,
+    sd: SocketDescriptor,
+    addr: SocketAddress,
+) -> MctpEmuResult<Vec<SupportedInterface>> {
+    use query_supported_interfaces::*;
+
+    let ctrl_hdr = ControlMsgHeader::new(
+        CommandCode::QuerySupportedInterfaces,
+        next_instance_id(),
+        false,
+        true,
+        false,
+    );
+    let req = Request { hdr: ctrl_hdr };
+    let (_, buf) = network.sendto(sd, Bytes::from(req), addr).await?;
+
+    let (_, resp) = ControlPayload::try_to_response::<Response>(buf)?;
+    resp.is_success()?;
+    Ok(resp.interfaces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{loopback::LoopbackBinding, simple_network::SimpleNetwork, *};
+    use crate::phys::smbus_types::SmbusPhysTransportHeader;
+    use anyhow::Result;
+    use bytes::BufMut;
+    use mctp_base_lib::base::TransportHeader;
+    use mctp_base_lib::control::enums::{PhysicalMediumIdentifier, PhysicalTransportBinding};
+    use std::time::Duration;
+    use tokio::sync::mpsc::Receiver;
+
+    fn bus_owner_addr() -> SocketAddress {
+        SocketAddress::Extended {
+            address: 0x08,
+            network: MCTP_NET_DEFAULT,
+            binding_id: 1,
+            phy_addr: 0x10,
+        }
+    }
+
+    fn request(dst_eid: u8, command_code: CommandCode, body: Bytes) -> Bytes {
+        let hdr = TransportHeader::builder()
+            .src_eid(0x08)
+            .dst_eid(dst_eid)
+            .msg_tag(2)
+            .tag_owner(true)
+            .start_of_msg(true)
+            .end_of_msg(true)
+            .build();
+        let ctrl_hdr = ControlMsgHeader::new(command_code, 1, false, true, false);
+        Bytes::from(ControlPayload::new(hdr, ctrl_hdr, body))
+    }
+
+    fn set_eid_request(dst_eid: u8, operation: set_eid::Operation, eid: u8) -> Bytes {
+        let ctrl_hdr = ControlMsgHeader::new(CommandCode::SetEndpointID, 1, false, true, false);
+        let req = set_eid::Request::new(ctrl_hdr, operation, eid);
+        request(dst_eid, CommandCode::SetEndpointID, Bytes::from(req))
+    }
+
+    fn empty_request(dst_eid: u8, command_code: CommandCode) -> Bytes {
+        let ctrl_hdr = ControlMsgHeader::new(command_code, 1, false, true, false);
+        request(
+            dst_eid,
+            command_code,
+            Bytes::from(EmptyRequest { hdr: ctrl_hdr }),
+        )
+    }
+
+    fn new_endpoint() -> Result<(Arc<SimpleEndpoint>, Receiver<Bytes>)> {
+        new_endpoint_with_config(SimpleEndpointConfig::default())
+    }
+
+    fn new_endpoint_with_config(
+        config: SimpleEndpointConfig,
+    ) -> Result<(Arc<SimpleEndpoint>, Receiver<Bytes>)> {
+        let (binding, rx) = LoopbackBinding::new();
+        let network = SimpleNetwork::new_mctp_network(Arc::new(tokio::sync::Mutex::new(binding)))?;
+        let endpoint = SimpleEndpoint::attach(network, config)?;
+        Ok((endpoint, rx))
+    }
+
+    #[tokio::test]
+    async fn test_set_and_get_eid() -> Result<()> {
+        let (endpoint, _) = new_endpoint()?;
+
+        let bytes = endpoint
+            .handle_request(
+                bus_owner_addr(),
+                set_eid_request(0, set_eid::Operation::SetEid, 0x20),
+            )?
+            .unwrap();
+        let (payload, resp) = ControlPayload::try_to_response::<set_eid::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        assert_eq!(resp.eid_setting, 0x20);
+        assert_eq!(payload.hdr.source_eid, 0x20);
+        assert!(endpoint.discovery().is_discovered(1));
+
+        let bytes = endpoint
+            .handle_request(
+                bus_owner_addr(),
+                set_eid_request(0x20, set_eid::Operation::SetEid, MCTP_ADDR_BCAST),
+            )?
+            .unwrap();
+        let (_, resp) = ControlPayload::try_to_response::<set_eid::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidData);
+        assert_eq!(endpoint.eid(), 0x20);
+
+        // dynamic endpoints have no static EID to go back to
+        let bytes = endpoint
+            .handle_request(
+                bus_owner_addr(),
+                set_eid_request(0x20, set_eid::Operation::ResetEid, 0),
+            )?
+            .unwrap();
+        let (_, resp) = ControlPayload::try_to_response::<set_eid::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidData);
+
+        let bytes = endpoint
+            .handle_request(
+                bus_owner_addr(),
+                empty_request(0x20, CommandCode::GetEndpointID),
+            )?
+            .unwrap();
+        let (_, resp) = ControlPayload::try_to_response::<get_eid::Response>(bytes)?;
+        assert_eq!(resp.eid, 0x20);
+        assert_eq!(resp.eid_type(), get_eid::EidType::Dynamic);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_strict_validation() -> Result<()> {
+        let (lenient, _) = new_endpoint()?;
+        let (strict, _) = new_endpoint_with_config(SimpleEndpointConfig {
+            strict_validation: true,
+            ..Default::default()
+        })?;
+
+        // integrity check bit on a control message
+        let mut bytes = empty_request(0, CommandCode::GetEndpointID).to_vec();
+        bytes[4] |= 0x80;
+        let bytes = Bytes::from(bytes);
+        let resp = lenient.handle_request(bus_owner_addr(), bytes.clone())?;
+        let (_, resp) = ControlPayload::try_to_response::<get_eid::Response>(resp.unwrap())?;
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        let resp = strict.handle_request(bus_owner_addr(), bytes)?.unwrap();
+        let (_, resp) = ControlPayload::try_to_response::<EmptyResponse>(resp)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidData);
+
+        // trailing byte after a Get Endpoint ID request
+        let mut bytes = empty_request(0, CommandCode::GetEndpointID).to_vec();
+        bytes.push(0x00);
+        let resp = strict
+            .handle_request(bus_owner_addr(), Bytes::from(bytes))?
+            .unwrap();
+        let (_, resp) = ControlPayload::try_to_response::<EmptyResponse>(resp)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidLength);
+
+        // truncated Set Endpoint ID request
+        let mut bytes = set_eid_request(0, set_eid::Operation::SetEid, 0x20).to_vec();
+        bytes.pop();
+        let resp = strict
+            .handle_request(bus_owner_addr(), Bytes::from(bytes))?
+            .unwrap();
+        let (_, resp) = ControlPayload::try_to_response::<EmptyResponse>(resp)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorInvalidLength);
+        assert_eq!(strict.eid(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_and_overridden_commands() -> Result<()> {
+        let (endpoint, _) = new_endpoint()?;
+
+        let bytes = endpoint
+            .handle_request(
+                bus_owner_addr(),
+                empty_request(0, CommandCode::AllocateEndpointIDs),
+            )?
+            .unwrap();
+        let (_, resp) = ControlPayload::try_to_response::<EmptyResponse>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorUnsupportedCmd);
+
+        endpoint.set_handler(
+            CommandCode::GetEndpointUUID,
+            Arc::new(|endpoint, _, payload, req| {
+                let ControlRequest::GetEndpointUUID(req) = req else {
+                    unreachable!()
+                };
+                let resp =
+                    get_uuid::Response::from(req, CompletionCode::ErrorNotReady, Uuid::nil());
+                Ok(Some(endpoint.respond(payload, resp.hdr, resp)))
+            }),
+        );
+        let bytes = endpoint
+            .handle_request(
+                bus_owner_addr(),
+                empty_request(0, CommandCode::GetEndpointUUID),
+            )?
+            .unwrap();
+        let (_, resp) = ControlPayload::try_to_response::<get_uuid::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::ErrorNotReady);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_answers_requests_from_network() -> Result<()> {
+        let (binding, mut rx) = LoopbackBinding::new();
+        let binding = Arc::new(tokio::sync::Mutex::new(binding));
+        let network = SimpleNetwork::new_mctp_network(binding.clone())?;
+        network.add_physical_binding(binding.clone()).await?;
+        let endpoint = SimpleEndpoint::attach(network, SimpleEndpointConfig::default())?;
+        tokio::spawn(endpoint.clone().run());
+
+        let req = empty_request(0, CommandCode::GetEndpointUUID);
+        let mut msg = BytesMut::new();
+        msg.put(Bytes::from(SmbusPhysTransportHeader::new(
+            0x20,
+            0x10,
+            req.len() as u8,
+        )));
+        msg.put(req);
+        let rx_callback = binding.lock().await.rx_callback();
+        rx_callback
+            .send(NetworkBindingCallbackMsg::Receive {
+                id: 1,
+                buf: msg.freeze(),
+            })
+            .await?;
+
+        let bytes = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await?
+            .unwrap();
+        let (payload, resp) = ControlPayload::try_to_response::<get_uuid::Response>(bytes)?;
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        assert_eq!(resp.uuid(), endpoint.uuid());
+        assert_eq!(payload.hdr.destination_eid, 0x08);
+        assert_eq!(payload.hdr.tag_owner(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_tx_rate_limit() -> Result<()> {
+        let (endpoint, _) = new_endpoint_with_config(SimpleEndpointConfig {
+            rate_limit: RateLimitCapabilities {
+                receive_buffer_size: 256,
+                max_receive_rate: 100,
+                max_tx_limit: RateLimit::new(8, 1000),
+                min_tx_rate: 10,
+            },
+            ..Default::default()
+        })?;
+        endpoint.handle_request(
+            bus_owner_addr(),
+            set_eid_request(0, set_eid::Operation::SetEid, 0x20),
+        )?;
+        let network = endpoint.network();
+        let request_tx_rate_limit = |tx_limit| {
+            let ctrl_hdr =
+                ControlMsgHeader::new(CommandCode::RequestTXRateLimit, 1, false, true, false);
+            let req = request_tx_rate_limit::Request::new(ctrl_hdr, tx_limit);
+            request(0x20, CommandCode::RequestTXRateLimit, req.into())
+        };
+
+        // out of range requests are clamped to what the transmitter supports
+        let bytes = endpoint
+            .handle_request(
+                bus_owner_addr(),
+                request_tx_rate_limit(RateLimit::new(16, 1)),
+            )?
+            .unwrap();
+        let (payload, resp) =
+            ControlPayload::try_to_response::<request_tx_rate_limit::Response>(bytes)?;
+        assert_eq!(payload.hdr.source_eid, 0x20);
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        assert_eq!(resp.present_tx_limit, RateLimit::new(8, 10));
+        assert_eq!(
+            network.tx_rate_limit(MCTP_NET_DEFAULT, 0x08),
+            Some(RateLimit::new(8, 10))
+        );
+
+        let bytes = endpoint
+            .handle_request(
+                bus_owner_addr(),
+                empty_request(0x20, CommandCode::QueryRateLimit),
+            )?
+            .unwrap();
+        let (_, resp) = ControlPayload::try_to_response::<query_rate_limit::Response>(bytes)?;
+        assert_eq!(resp.receive_buffer_size, 256);
+        assert_eq!(resp.present_tx_limit, RateLimit::new(8, 10));
+
+        // an unlimited rate lifts the limit
+        endpoint.handle_request(
+            bus_owner_addr(),
+            request_tx_rate_limit(RateLimit::default()),
+        )?;
+        assert_eq!(network.tx_rate_limit(MCTP_NET_DEFAULT, 0x08), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_supported_interfaces() -> Result<()> {
+        let binding = Arc::new(tokio::sync::Mutex::new(LoopbackBinding::default()));
+        let network = SimpleNetwork::new_mctp_network(binding.clone())?;
+        network.add_physical_binding(binding).await?;
+        let second = Arc::new(tokio::sync::Mutex::new(LoopbackBinding::default()));
+        network.add_physical_binding(second).await?;
+
+        let payload =
+            ControlPayload::try_from(empty_request(0x20, CommandCode::QuerySupportedInterfaces))?;
+        let resp = handle_query_supported_interfaces(&network, 0x20, payload)?;
+        assert_eq!(resp.response_code(), CompletionCode::Success);
+        let interface = SupportedInterface::new(
+            PhysicalTransportBinding::VendorDefined,
+            PhysicalMediumIdentifier::Unspecified,
+            0x20,
+        );
+        assert_eq!(resp.interfaces, vec![interface, interface]);
+
+        Ok(())
+    }
+}
@@ -1,7 +1,40 @@
 use crate::network::ClientCallbackMsg;
-use crate::OneshotResponder;
+use crate::{MctpEmuResult, OneshotResponder};
+use anyhow::anyhow;
 use bytes::Bytes;
-use tokio::sync::oneshot;
+use mctp_base_lib::control::paging::NO_MORE_ENTRIES;
+use std::future::Future;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tokio::sync::{mpsc, oneshot};
+
+static NEXT_INSTANCE_ID: AtomicU8 = AtomicU8::new(0);
+
+/// Instance ID for the next control request sent by the library's requester helpers
+pub(crate) fn next_instance_id() -> u8 {
+    NEXT_INSTANCE_ID.fetch_add(1, Ordering::SeqCst) & 0x1f
+}
+
+/// Reads every page of a paged command, starting at entry handle 0. `fetch` requests the page at
+/// an entry handle and returns the handle of the next page along with the entries.
+pub(crate) async fn read_pages<T, F, Fut>(command: &str, mut fetch: F) -> MctpEmuResult<Vec<T>>
+where
+    F: FnMut(u8) -> Fut,
+    Fut: Future<Output = MctpEmuResult<(u8, Vec<T>)>>,
+{
+    let mut entries = Vec::new();
+    let mut entry_handle = 0;
+    while entry_handle != NO_MORE_ENTRIES {
+        let (next_entry_handle, page) = fetch(entry_handle).await?;
+        if next_entry_handle != NO_MORE_ENTRIES && next_entry_handle <= entry_handle {
+            return Err(
+                anyhow!("{command} entry handle didn't advance: {next_entry_handle:#04x}").into(),
+            );
+        }
+        entries.extend(page);
+        entry_handle = next_entry_handle;
+    }
+    Ok(entries)
+}
 
 #[derive(Debug, Default, PartialEq, Ord, PartialOrd, Eq)]
 #[allow(non_camel_case_types, unused)]
@@ -14,3 +47,5 @@ pub struct MsgFlowTag {
 
 pub type MctpFlow = (MsgFlowTag, oneshot::Sender<ClientCallbackMsg>);
 pub type MctpFlowList = Vec<MctpFlow>;
+pub type MctpBroadcastFlow = (MsgFlowTag, mpsc::Sender<ClientCallbackMsg>);
+pub type MctpBroadcastFlowList = Vec<MctpBroadcastFlow>;
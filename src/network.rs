@@ -1,5 +1,13 @@
 mod error;
+mod integrity;
+#[cfg(test)]
+pub(crate) mod loopback;
+mod packet;
+mod rate_limit;
+mod reassembly;
+mod routing;
 pub mod simple_network;
+mod tag_alloc;
 mod types;
 pub mod virtual_network;
 
@@ -9,6 +9,12 @@ pub enum Error {
     #[error("invalid physical binding descriptor")]
     InvalidBindingError { binding_id: BindingDescriptor },
 
+    #[error("no route to EID {eid:#04x} on network {net:?}")]
+    RouteNotFoundError { net: u32, eid: u8 },
+
+    #[error("no response from EID {eid:#04x} after {attempts} attempts")]
+    TimeoutError { eid: u8, attempts: u16 },
+
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 
@@ -0,0 +1,85 @@
+use bytes::{BufMut, Bytes, BytesMut};
+
+use mctp_base_lib::control::enums::MessageType;
+
+/// Size of the message integrity check (MIC) trailing messages with the IC bit set
+pub(crate) const MIC_SIZE: usize = 4;
+
+/// Integrity check bit of the message type byte
+const IC_BIT: u8 = 0x80;
+
+/// Whether `msg`, starting at its message type byte, ends with a MIC. Control messages never carry
+/// one, even with the IC bit set.
+pub(crate) fn has_mic(msg: &[u8]) -> bool {
+    match msg.first() {
+        Some(msg_type) => {
+            msg_type & IC_BIT != 0 && msg_type & !IC_BIT != MessageType::Control as u8
+        }
+        None => false,
+    }
+}
+
+/// CRC-32C over the message type byte and message body, as used by the message types that set
+/// the IC bit.
+pub(crate) fn message_integrity_check(msg: &[u8]) -> u32 {
+    crc32c::crc32c(msg)
+}
+
+/// Appends the MIC to `msg` when its IC bit asks for one. The MIC is sent least significant byte
+/// first.
+pub(crate) fn append_mic(msg: Bytes) -> Bytes {
+    if !has_mic(&msg) {
+        return msg;
+    }
+    let mut buf = BytesMut::with_capacity(msg.len() + MIC_SIZE);
+    buf.put_slice(&msg);
+    buf.put_u32_le(message_integrity_check(&msg));
+    buf.freeze()
+}
+
+/// Checks the MIC trailing `msg` and removes it. Returns `None` when the MIC doesn't match (or
+/// the message is too short to hold one).
+pub(crate) fn strip_mic(mut msg: Bytes) -> Option<Bytes> {
+    if !has_mic(&msg) {
+        return Some(msg);
+    }
+    if msg.len() < 1 + MIC_SIZE {
+        return None;
+    }
+    let mic = msg.split_off(msg.len() - MIC_SIZE);
+    let expected = message_integrity_check(&msg);
+    match u32::from_le_bytes([mic[0], mic[1], mic[2], mic[3]]) == expected {
+        true => Some(msg),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mic_round_trip() {
+        // vendor defined (PCI) message with the IC bit set
+        let msg = Bytes::from_static(&[0xfe, 0x12, 0x34, 0x01, 0x02]);
+        let sent = append_mic(msg.clone());
+        assert_eq!(sent.len(), msg.len() + MIC_SIZE);
+        assert_eq!(&sent[msg.len()..], &crc32c::crc32c(&msg).to_le_bytes());
+        assert_eq!(strip_mic(sent.clone()), Some(msg));
+
+        let mut corrupted = sent.to_vec();
+        corrupted[2] ^= 0x01;
+        assert_eq!(strip_mic(Bytes::from(corrupted)), None);
+        assert_eq!(strip_mic(Bytes::from_static(&[0x81, 0x00])), None);
+    }
+
+    #[test]
+    fn test_no_mic_without_ic_bit() {
+        let pldm = Bytes::from_static(&[0x01, 0x80, 0x00]);
+        assert_eq!(append_mic(pldm.clone()), pldm);
+        // control messages never carry a MIC
+        let control = Bytes::from_static(&[0x80, 0x81, 0x02]);
+        assert_eq!(append_mic(control.clone()), control);
+        assert_eq!(strip_mic(control.clone()), Some(control));
+    }
+}
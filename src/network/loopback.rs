@@ -0,0 +1,48 @@
+use bytes::Bytes;
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task::JoinHandle;
+
+use crate::network::{NetworkBinding, NetworkBindingCallbackMsg};
+use crate::{MctpEmuEmptyResult, MctpEmuResult};
+
+/// Test binding that hands every transmitted message to the test and lets it inject received
+/// ones. The default binding drops what it transmits.
+#[derive(Debug, Default)]
+pub(crate) struct LoopbackBinding {
+    transmitted: Option<Sender<Bytes>>,
+    rx_callback: Option<Sender<NetworkBindingCallbackMsg>>,
+}
+
+impl LoopbackBinding {
+    pub(crate) fn new() -> (Self, Receiver<Bytes>) {
+        let (transmitted, rx) = mpsc::channel(16);
+        let binding = Self {
+            transmitted: Some(transmitted),
+            rx_callback: None,
+        };
+        (binding, rx)
+    }
+
+    /// Channel the network receives on, once the binding was added to one
+    pub(crate) fn rx_callback(&self) -> Sender<NetworkBindingCallbackMsg> {
+        self.rx_callback.clone().expect("binding isn't bound")
+    }
+}
+
+impl NetworkBinding for LoopbackBinding {
+    fn transmit(&self, buf: Bytes, _phy_addr: u64) -> MctpEmuEmptyResult {
+        if let Some(transmitted) = self.transmitted.as_ref() {
+            transmitted.try_send(buf).unwrap();
+        }
+        Ok(())
+    }
+
+    fn bind(
+        &mut self,
+        _id: u64,
+        rx_callback: Sender<NetworkBindingCallbackMsg>,
+    ) -> MctpEmuResult<JoinHandle<MctpEmuEmptyResult>> {
+        self.rx_callback = Some(rx_callback);
+        Ok(tokio::spawn(async { Ok(()) }))
+    }
+}
@@ -0,0 +1,82 @@
+use bytes::{BufMut, Bytes, BytesMut};
+
+use mctp_base_lib::base::TransportHeader;
+
+use crate::network::MCTP_BASELINE_MTU;
+
+/// Packet sequence numbers are 2 bits wide
+const PACKET_SEQ_MODULO: usize = 4;
+
+/// Splits `msg` (message type byte onwards) into packets carrying at most `mtu` bytes of it, each
+/// starting with a copy of `hdr`. Only the first packet has SOM set and only the last one has EOM;
+/// sequence numbers start at 0 and wrap after 3. An MTU below the baseline is raised to it, since
+/// every medium has to carry baseline sized packets.
+pub(crate) fn packetize(hdr: TransportHeader, msg: Bytes, mtu: usize) -> Vec<Bytes> {
+    let mtu = mtu.max(MCTP_BASELINE_MTU as usize);
+    let num_packets = msg.len().div_ceil(mtu).max(1);
+    (0..num_packets)
+        .map(|index| {
+            let chunk = msg.slice(index * mtu..msg.len().min((index + 1) * mtu));
+            let mut hdr = hdr;
+            hdr.set_som((index == 0).into());
+            hdr.set_eom((index == num_packets - 1).into());
+            hdr.set_packet_seq((index % PACKET_SEQ_MODULO) as u8);
+
+            let mut packet = BytesMut::with_capacity(std::mem::size_of::<TransportHeader>() + mtu);
+            packet.put(Bytes::from(hdr));
+            packet.put(chunk);
+            packet.freeze()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> TransportHeader {
+        TransportHeader::builder()
+            .src_eid(0x08)
+            .dst_eid(0x20)
+            .msg_tag(3)
+            .tag_owner(true)
+            .build()
+    }
+
+    #[test]
+    fn test_packetize() {
+        let msg: Bytes = (0..=255u8)
+            .cycle()
+            .take(64 * 5 + 10)
+            .collect::<Vec<_>>()
+            .into();
+        let packets = packetize(header(), msg.clone(), 64);
+        assert_eq!(packets.len(), 6);
+
+        let mut reassembled = BytesMut::new();
+        for (index, packet) in packets.iter().enumerate() {
+            let hdr = TransportHeader::try_from(packet.clone()).unwrap();
+            assert_eq!(hdr.som(), (index == 0) as u8);
+            assert_eq!(hdr.eom(), (index == 5) as u8);
+            assert_eq!(hdr.packet_seq(), (index % 4) as u8);
+            assert_eq!(hdr.msg_tag(), 3);
+            assert_eq!(hdr.tag_owner(), 1);
+            reassembled.put(packet.slice(4..));
+        }
+        assert_eq!(packets[0].len(), 4 + 64);
+        assert_eq!(packets[5].len(), 4 + 10);
+        assert_eq!(reassembled.freeze(), msg);
+    }
+
+    #[test]
+    fn test_single_packet() {
+        // a message filling the MTU exactly doesn't get an empty trailing packet
+        let packets = packetize(header(), Bytes::from(vec![0x7e; 64]), 64);
+        assert_eq!(packets.len(), 1);
+        let hdr = TransportHeader::try_from(packets[0].clone()).unwrap();
+        assert_eq!((hdr.som(), hdr.eom(), hdr.packet_seq()), (1, 1, 0));
+
+        // MTUs below the baseline aren't honoured
+        assert_eq!(packetize(header(), Bytes::from(vec![0; 64]), 0).len(), 1);
+    }
+}
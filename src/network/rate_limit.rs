@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use mctp_base_lib::control::models::RateLimit;
+
+/// Transmit limiters of a network, keyed by destination network and EID
+#[derive(Debug, Default)]
+pub(crate) struct TxRateLimiters {
+    limiters: Mutex<HashMap<(u32, u8), TxRateLimiter>>,
+}
+
+impl TxRateLimiters {
+    /// Limits the packets sent to `eid` on network `net`. `None` or an unlimited rate lifts the
+    /// limit.
+    pub(crate) fn set(&self, net: u32, eid: u8, limit: Option<RateLimit>) {
+        let mut limiters = self.limiters.lock().unwrap();
+        match limit {
+            Some(limit) if limit.is_limited() => {
+                limiters.insert((net, eid), TxRateLimiter::new(limit));
+            }
+            _ => {
+                limiters.remove(&(net, eid));
+            }
+        }
+    }
+
+    pub(crate) fn limit(&self, net: u32, eid: u8) -> Option<RateLimit> {
+        self.limiters
+            .lock()
+            .unwrap()
+            .get(&(net, eid))
+            .map(TxRateLimiter::limit)
+    }
+
+    /// Takes a token for one packet to `eid` and returns how long the sender has to wait before
+    /// sending it.
+    pub(crate) fn reserve(&self, net: u32, eid: u8) -> Duration {
+        match self.limiters.lock().unwrap().get_mut(&(net, eid)) {
+            Some(limiter) => limiter.reserve(),
+            None => Duration::ZERO,
+        }
+    }
+}
+
+/// Token bucket enforcing a [`RateLimit`] on the packets sent to one endpoint. The bucket holds up
+/// to `burst_size` packets and refills at `max_rate` packets per second. A packet sent on an empty
+/// bucket borrows its token, so concurrent senders queue up behind each other instead of all
+/// waking up at once.
+#[derive(Debug)]
+pub(crate) struct TxRateLimiter {
+    limit: RateLimit,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TxRateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: Self::capacity(&limit),
+            last_refill: Instant::now(),
+        }
+    }
+
+    pub(crate) fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Takes a token for one packet and returns how long the sender has to wait before sending it.
+    pub(crate) fn reserve(&mut self) -> Duration {
+        self.reserve_at(Instant::now())
+    }
+
+    fn reserve_at(&mut self, now: Instant) -> Duration {
+        if !self.limit.is_limited() {
+            return Duration::ZERO;
+        }
+
+        let rate = self.limit.max_rate as f64;
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * rate).min(Self::capacity(&self.limit));
+        self.last_refill = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+
+    fn capacity(limit: &RateLimit) -> f64 {
+        limit.burst_size.max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_throttle() {
+        let mut limiter = TxRateLimiter::new(RateLimit::new(2, 10));
+        let start = limiter.last_refill;
+
+        assert_eq!(limiter.reserve_at(start), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(start), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(start), Duration::from_millis(100));
+        assert_eq!(limiter.reserve_at(start), Duration::from_millis(200));
+
+        // The bucket refills at the negotiated rate, up to the burst size
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.reserve_at(later), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(later), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(later), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_unlimited() {
+        let mut limiter = TxRateLimiter::new(RateLimit::new(0, 0));
+        let now = limiter.last_refill;
+        for _ in 0..100 {
+            assert_eq!(limiter.reserve_at(now), Duration::ZERO);
+        }
+    }
+}
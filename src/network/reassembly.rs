@@ -0,0 +1,228 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use mctp_base_lib::base::TransportHeader;
+
+use crate::network::{ReassemblyStats, MCTP_MAX_MESSAGE_SIZE, MCTP_REASSEMBLY_TIMEOUT};
+
+const TRANSPORT_HDR_SIZE: usize = std::mem::size_of::<TransportHeader>();
+
+/// Messages being reassembled are told apart by source EID, message tag and tag owner bit
+type ReassemblyKey = (u8, u8, bool);
+
+#[derive(Debug)]
+struct PartialMessage {
+    /// Transport header of the first packet
+    hdr: TransportHeader,
+    body: BytesMut,
+    next_seq: u8,
+    last_packet: Instant,
+}
+
+/// Rebuilds messages out of the packets received by a network, dropping the ones that arrive out
+/// of sequence, grow too large or stall.
+#[derive(Debug)]
+pub(crate) struct Reassembler {
+    messages: HashMap<ReassemblyKey, PartialMessage>,
+    max_message_size: usize,
+    timeout: Duration,
+    stats: ReassemblyStats,
+}
+
+impl Default for Reassembler {
+    fn default() -> Self {
+        Self::new(MCTP_MAX_MESSAGE_SIZE, MCTP_REASSEMBLY_TIMEOUT)
+    }
+}
+
+impl Reassembler {
+    pub(crate) fn new(max_message_size: usize, timeout: Duration) -> Self {
+        Self {
+            messages: Default::default(),
+            max_message_size,
+            timeout,
+            stats: Default::default(),
+        }
+    }
+
+    pub(crate) fn stats(&self) -> ReassemblyStats {
+        self.stats
+    }
+
+    /// Adds a packet (transport header onwards) and returns the message it completes, if any. The
+    /// message starts with the transport header of its first packet, with EOM set.
+    pub(crate) fn receive(&mut self, packet: Bytes) -> Option<Bytes> {
+        self.receive_at(packet, Instant::now())
+    }
+
+    /// Abandons the messages whose next packet is overdue.
+    pub(crate) fn expire(&mut self) {
+        self.expire_at(Instant::now())
+    }
+
+    fn receive_at(&mut self, packet: Bytes, now: Instant) -> Option<Bytes> {
+        self.expire_at(now);
+
+        let hdr = TransportHeader::try_from(packet.clone()).ok()?;
+        let body = packet.slice(TRANSPORT_HDR_SIZE..);
+        let key = (hdr.source_eid, hdr.msg_tag(), hdr.tag_owner() != 0);
+
+        let mut message = match (hdr.som() != 0, self.messages.remove(&key)) {
+            (true, interrupted) => {
+                // a new message with the same tag replaces the one in progress
+                if interrupted.is_some() {
+                    self.stats.interrupted += 1;
+                }
+                PartialMessage {
+                    hdr,
+                    body: BytesMut::new(),
+                    next_seq: hdr.packet_seq(),
+                    last_packet: now,
+                }
+            }
+            (false, Some(message)) => message,
+            (false, None) => {
+                self.stats.missing_som += 1;
+                return None;
+            }
+        };
+
+        if hdr.packet_seq() != message.next_seq {
+            self.stats.sequence_errors += 1;
+            return None;
+        }
+        if message.body.len() + body.len() > self.max_message_size {
+            self.stats.too_large += 1;
+            return None;
+        }
+        message.body.put(body);
+        message.next_seq = (message.next_seq + 1) % 4;
+        message.last_packet = now;
+
+        if hdr.eom() == 0 {
+            self.messages.insert(key, message);
+            return None;
+        }
+        let mut hdr = message.hdr;
+        hdr.set_eom(1);
+        let mut buf = BytesMut::with_capacity(TRANSPORT_HDR_SIZE + message.body.len());
+        buf.put(Bytes::from(hdr));
+        buf.put(message.body);
+        Some(buf.freeze())
+    }
+
+    fn expire_at(&mut self, now: Instant) {
+        let timeout = self.timeout;
+        let before = self.messages.len();
+        self.messages
+            .retain(|_, message| now.duration_since(message.last_packet) < timeout);
+        self.stats.timeouts += (before - self.messages.len()) as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(src_eid: u8, som: bool, eom: bool, seq: u8, body: &[u8]) -> Bytes {
+        let mut hdr = TransportHeader::builder()
+            .src_eid(src_eid)
+            .dst_eid(0x20)
+            .msg_tag(2)
+            .tag_owner(true)
+            .start_of_msg(som)
+            .end_of_msg(eom)
+            .build();
+        hdr.set_packet_seq(seq);
+        let mut buf = BytesMut::new();
+        buf.put(Bytes::from(hdr));
+        buf.put_slice(body);
+        buf.freeze()
+    }
+
+    #[test]
+    fn test_reassembly() {
+        let mut reassembler = Reassembler::default();
+        let now = Instant::now();
+
+        let single = packet(0x08, true, true, 1, &[0x7e, 0x01]);
+        assert_eq!(reassembler.receive_at(single.clone(), now), Some(single));
+
+        // sequence numbers wrap and can start anywhere
+        assert_eq!(
+            reassembler.receive_at(packet(0x08, true, false, 3, &[0x7e, 0x01]), now),
+            None
+        );
+        assert_eq!(
+            reassembler.receive_at(packet(0x09, true, false, 0, &[0x7f]), now),
+            None
+        );
+        assert_eq!(
+            reassembler.receive_at(packet(0x08, false, false, 0, &[0x02]), now),
+            None
+        );
+        let msg = reassembler
+            .receive_at(packet(0x08, false, true, 1, &[0x03]), now)
+            .unwrap();
+        let hdr = TransportHeader::try_from(msg.clone()).unwrap();
+        assert_eq!((hdr.som(), hdr.eom(), hdr.packet_seq()), (1, 1, 3));
+        assert_eq!(&msg[4..], &[0x7e, 0x01, 0x02, 0x03]);
+
+        // packets of another source EID are kept apart
+        let msg = reassembler
+            .receive_at(packet(0x09, false, true, 1, &[0x04]), now)
+            .unwrap();
+        assert_eq!(&msg[4..], &[0x7f, 0x04]);
+        assert_eq!(reassembler.stats(), ReassemblyStats::default());
+    }
+
+    #[test]
+    fn test_reassembly_failures() {
+        let mut reassembler = Reassembler::new(8, Duration::from_millis(100));
+        let now = Instant::now();
+
+        // middle packet without a message in progress
+        assert_eq!(
+            reassembler.receive_at(packet(0x08, false, false, 1, &[0]), now),
+            None
+        );
+        assert_eq!(reassembler.stats().missing_som, 1);
+
+        // sequence gap drops the message, so its last packet is missing its SOM
+        reassembler.receive_at(packet(0x08, true, false, 0, &[0]), now);
+        assert_eq!(
+            reassembler.receive_at(packet(0x08, false, false, 2, &[0]), now),
+            None
+        );
+        assert_eq!(
+            reassembler.receive_at(packet(0x08, false, true, 3, &[0]), now),
+            None
+        );
+        assert_eq!(reassembler.stats().sequence_errors, 1);
+        assert_eq!(reassembler.stats().missing_som, 2);
+
+        // message growing past the maximum size
+        reassembler.receive_at(packet(0x08, true, false, 0, &[0; 6]), now);
+        assert_eq!(
+            reassembler.receive_at(packet(0x08, false, true, 1, &[0; 3]), now),
+            None
+        );
+        assert_eq!(reassembler.stats().too_large, 1);
+
+        // new SOM while a message is in progress
+        reassembler.receive_at(packet(0x08, true, false, 0, &[0]), now);
+        reassembler.receive_at(packet(0x08, true, false, 0, &[1]), now);
+        assert_eq!(reassembler.stats().interrupted, 1);
+
+        // stalled message
+        let later = now + Duration::from_millis(100);
+        assert_eq!(
+            reassembler.receive_at(packet(0x08, false, true, 1, &[0]), later),
+            None
+        );
+        assert_eq!(reassembler.stats().timeouts, 1);
+        assert_eq!(reassembler.stats().missing_som, 3);
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use crate::network::{
+    BindingDescriptor, BindingInfo, Neighbour, NeighbourHandle, Route, RouteHandle,
+};
+
+/// Routes, neighbours and binding properties known to a network
+#[derive(Debug, Default)]
+pub(crate) struct RoutingTable {
+    routes: RwLock<Vec<RouteHandle>>,
+    neighbours: RwLock<Vec<NeighbourHandle>>,
+    binding_info: RwLock<HashMap<BindingDescriptor, BindingInfo>>,
+}
+
+impl RoutingTable {
+    /// Adds a route, replacing the one covering the same EID range.
+    pub(crate) fn add_route(&self, route: Route) {
+        let mut routes = self.routes.write().unwrap();
+        routes.retain(|existing| !existing.same_range(&route));
+        routes.push(Arc::new(route));
+    }
+
+    /// Adds a neighbour, replacing the one with the same network and EID.
+    pub(crate) fn add_neighbour(&self, neighbour: Neighbour) {
+        let mut neighbours = self.neighbours.write().unwrap();
+        neighbours.retain(|existing| !existing.matches(neighbour.net(), neighbour.eid()));
+        neighbours.push(Arc::new(neighbour));
+    }
+
+    pub(crate) fn set_binding_info(&self, binding_id: BindingDescriptor, info: BindingInfo) {
+        self.binding_info.write().unwrap().insert(binding_id, info);
+    }
+
+    pub(crate) fn binding_info(&self, binding_id: BindingDescriptor) -> Option<BindingInfo> {
+        self.binding_info.read().unwrap().get(&binding_id).copied()
+    }
+
+    /// Properties of every binding, ordered by binding ID.
+    pub(crate) fn bindings(&self) -> Vec<(BindingDescriptor, BindingInfo)> {
+        let mut bindings: Vec<_> = self
+            .binding_info
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(id, info)| (*id, *info))
+            .collect();
+        bindings.sort_by_key(|(id, _)| *id);
+        bindings
+    }
+
+    /// Routes of network `net`, in the order they were added.
+    pub(crate) fn routes(&self, net: u32) -> Vec<RouteHandle> {
+        self.routes
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|route| route.net() == net)
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn route_lookup(&self, net: u32, eid: u8) -> Option<RouteHandle> {
+        self.routes
+            .read()
+            .unwrap()
+            .iter()
+            .find(|route| route.matches(net, eid))
+            .cloned()
+    }
+
+    pub(crate) fn neighbour_lookup(&self, net: u32, eid: u8) -> Option<NeighbourHandle> {
+        self.neighbours
+            .read()
+            .unwrap()
+            .iter()
+            .find(|neighbour| neighbour.matches(net, eid))
+            .cloned()
+    }
+
+    pub(crate) fn neighbour_lookup_by_hw_addr(
+        &self,
+        net: u32,
+        binding_id: BindingDescriptor,
+        hw_addr: &[u8],
+    ) -> Option<NeighbourHandle> {
+        self.neighbours
+            .read()
+            .unwrap()
+            .iter()
+            .find(|neighbour| neighbour.matches_hw_addr(net, binding_id, hw_addr))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{NeighbourSource, MCTP_BASELINE_MTU, MCTP_NET_DEFAULT};
+
+    #[test]
+    fn test_entries_replace_their_predecessors() {
+        let table = RoutingTable::default();
+        table.add_route(Route::new(0x10, 0x1f, MCTP_NET_DEFAULT, 64, 1, None));
+        table.add_route(Route::new(0x10, 0x1f, MCTP_NET_DEFAULT, 128, 2, None));
+        table.add_route(Route::new(0x20, 0x20, 2, MCTP_BASELINE_MTU, 1, None));
+        assert_eq!(table.routes(MCTP_NET_DEFAULT).len(), 1);
+        assert_eq!(
+            table.route_lookup(MCTP_NET_DEFAULT, 0x15).unwrap().mtu(),
+            128
+        );
+        assert!(table.route_lookup(MCTP_NET_DEFAULT, 0x20).is_none());
+
+        let neighbour = |hw_addr| {
+            Neighbour::new(
+                0x15,
+                MCTP_NET_DEFAULT,
+                1,
+                NeighbourSource::Static,
+                &[hw_addr],
+            )
+        };
+        table.add_neighbour(neighbour(0x30));
+        table.add_neighbour(neighbour(0x32));
+        assert!(table
+            .neighbour_lookup_by_hw_addr(MCTP_NET_DEFAULT, 1, &[0x30])
+            .is_none());
+        assert_eq!(
+            table
+                .neighbour_lookup_by_hw_addr(MCTP_NET_DEFAULT, 1, &[0x32])
+                .unwrap()
+                .eid(),
+            0x15
+        );
+        assert!(table.neighbour_lookup(MCTP_NET_DEFAULT, 0x15).is_some());
+    }
+}
@@ -18,20 +18,28 @@ use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
 use tokio::net::UdpSocket;
 use tokio::sync::mpsc::{Receiver, Sender};
-use tokio::sync::{mpsc, oneshot, MutexGuard};
+use tokio::sync::{mpsc, oneshot, MutexGuard, Notify};
 use tokio::task::JoinHandle;
 use tracing::{event, Level};
+use uuid::Uuid;
 
 use mctp_base_lib::{
     base::*,
     control::{
         enums::{CommandCode, CompletionCode, MessageType},
         get_eid::{EidType, EndpointType},
+        models::RateLimit,
         ControlMsgReponseStatus, *,
     },
 };
 
-use crate::endpoint::{MctpFlowList, MsgFlowTag};
+use crate::endpoint::{MctpBroadcastFlowList, MctpFlowList, MsgFlowTag};
+use crate::network::integrity;
+use crate::network::packet::packetize;
+use crate::network::rate_limit::TxRateLimiters;
+use crate::network::reassembly::Reassembler;
+use crate::network::routing::RoutingTable;
+use crate::network::tag_alloc::TagAllocator;
 use crate::phys::smbus_types::SmbusPhysTransportHeader;
 use crate::{
     network::{types::*, Error, NetDevice, Result},
@@ -41,12 +49,23 @@ use crate::{
 #[derive(Debug, derive_builder::Builder)]
 #[builder(private, pattern = "owned")]
 pub struct SimpleNetwork {
+    network_id: Uuid,
     clients: Arc<RwLock<HashMap<i32, ClientHandle>>>,
     num_clients: AtomicI32,
-    phys_bindings: NetworkBindingHandle,
+    /// Bindings by ID. The binding the network is created with stands in as binding 1 until the
+    /// first binding is added.
+    phys_bindings: Arc<RwLock<HashMap<BindingDescriptor, NetworkBindingHandle>>>,
+    next_binding_id: AtomicU64,
+    routing: Arc<RoutingTable>,
     callback_handles: Arc<RwLock<Vec<JoinHandle<MctpEmuEmptyResult>>>>,
     rx_callback: Sender<NetworkBindingCallbackMsg>,
     flows: Arc<Mutex<MctpFlowList>>,
+    broadcast_flows: Arc<Mutex<MctpBroadcastFlowList>>,
+    tx_limiters: Arc<TxRateLimiters>,
+    stats: Arc<Mutex<NetworkStats>>,
+    reassembler: Arc<Mutex<Reassembler>>,
+    tags: Arc<Mutex<TagAllocator>>,
+    tag_released: Arc<Notify>,
 }
 
 fn create_tag(bytes: Bytes) -> Option<MsgFlowTag> {
@@ -69,20 +88,37 @@ fn create_tag(bytes: Bytes) -> Option<MsgFlowTag> {
 
 impl SimpleNetwork {
     pub fn new_mctp_network(binding: NetworkBindingHandle) -> MctpEmuResult<MctpNetworkHandle> {
-        let network = SimpleNetwork::new(binding)?;
+        Self::new_mctp_network_with_id(binding, Uuid::new_v4())
+    }
+
+    /// Creates a network with a fixed identity instead of a random one
+    pub fn new_mctp_network_with_id(
+        binding: NetworkBindingHandle,
+        network_id: Uuid,
+    ) -> MctpEmuResult<MctpNetworkHandle> {
+        let network = SimpleNetwork::new(binding, network_id)?;
         Ok(network)
     }
 
-    fn new(binding: NetworkBindingHandle) -> MctpEmuResult<Arc<Self>> {
+    fn new(binding: NetworkBindingHandle, network_id: Uuid) -> MctpEmuResult<Arc<Self>> {
         let (sender, mut receiver) = mpsc::channel::<NetworkBindingCallbackMsg>(32);
 
         let builder = SimpleNetworkBuilder::default()
-            .phys_bindings(binding)
+            .network_id(network_id)
+            .phys_bindings(Arc::new(RwLock::new(HashMap::from([(1, binding)]))))
+            .next_binding_id(AtomicU64::new(1))
+            .routing(Default::default())
             .clients(Default::default())
             .num_clients(Default::default())
             .callback_handles(Default::default())
             .rx_callback(sender)
-            .flows(Default::default());
+            .flows(Default::default())
+            .broadcast_flows(Default::default())
+            .tx_limiters(Default::default())
+            .stats(Default::default())
+            .reassembler(Default::default())
+            .tags(Default::default())
+            .tag_released(Default::default());
         let mut network: SimpleNetwork = match builder.build() {
             Ok(n) => n,
             Err(err) => {
@@ -129,7 +165,10 @@ impl SimpleNetwork {
                             }
                         };
 
-                        let buf = buf.slice(4..);
+                        let buf = match self.reassembler.lock().unwrap().receive(buf.slice(4..)) {
+                            Some(buf) => buf,
+                            None => continue,
+                        };
 
                         let recv_tag = match create_tag(buf.clone()) {
                             None => {
@@ -139,12 +178,20 @@ impl SimpleNetwork {
                             Some(tag) => tag,
                         };
 
+                        let buf = match self.check_integrity(buf) {
+                            Some(buf) => buf,
+                            None => continue,
+                        };
+
+                        let phy_addr = transport_hdr.src_addr_7bit();
+                        self.learn_neighbour(recv_tag.src_eid, id, phy_addr);
+
                         let response = ClientCallbackMsg::Receive {
                             addr: SocketAddress::Extended {
-                                address: 0,
-                                network: 1,
+                                address: recv_tag.src_eid,
+                                network: MCTP_NET_DEFAULT,
                                 binding_id: id,
-                                phy_addr: 0,
+                                phy_addr: phy_addr as u64,
                             },
                             buf,
                         };
@@ -158,6 +205,7 @@ impl SimpleNetwork {
                                 if tag.msg_tag == recv_tag.msg_tag
                                     && tag.tag_owner != recv_tag.tag_owner
                                     && tag.dest_eid == recv_tag.src_eid
+                                    && tag.src_eid == recv_tag.dest_eid
                                 {
                                     break;
                                 }
@@ -166,14 +214,31 @@ impl SimpleNetwork {
 
                             // TODO: build response
                             if index != flows_inflight.len() {
-                                let (_, resp) = flows_inflight.remove(index);
-                                resp.send(response).unwrap();
+                                let (tag, resp) = flows_inflight.remove(index);
+                                self.release_tag(tag.src_eid, tag.dest_eid, tag.msg_tag);
+                                if resp.send(response).is_err() {
+                                    tracing::warn!("requester went away, dropping response");
+                                }
                                 continue;
                             }
                         }
 
-                        // TODO: send to client channel
-                        tracing::warn!("sending to client is not yet supported");
+                        // responses to a broadcast can come from any EID
+                        {
+                            let broadcast_flows = self.broadcast_flows.lock().unwrap();
+                            let flow = broadcast_flows.iter().find(|(tag, _)| {
+                                tag.msg_tag == recv_tag.msg_tag
+                                    && tag.tag_owner != recv_tag.tag_owner
+                            });
+                            if let Some((_, sender)) = flow {
+                                if sender.try_send(response).is_err() {
+                                    tracing::warn!("dropping broadcast response");
+                                }
+                                continue;
+                            }
+                        }
+
+                        self.deliver_to_client(recv_tag.dest_eid, response);
                     }
                 }
             }
@@ -181,10 +246,10 @@ impl SimpleNetwork {
     }
 
     fn get_binding(&self, binding_id: u64) -> Result<NetworkBindingHandle> {
-        if binding_id != 1 {
-            return Err(Error::InvalidBindingError { binding_id });
+        match self.phys_bindings.read().unwrap().get(&binding_id) {
+            Some(binding) => Ok(binding.clone()),
+            None => Err(Error::InvalidBindingError { binding_id }),
         }
-        Ok(self.phys_bindings.clone())
     }
 
     fn get_client(&self, sd: i32) -> MctpEmuResult<ClientHandle> {
@@ -193,6 +258,207 @@ impl SimpleNetwork {
             None => Err(Error::InvalidSocketError { sd }.into()),
         }
     }
+
+    /// Splits a request from `src_eid` into packets of at most `mtu` bytes, each starting with its
+    /// transport header.
+    fn build_packets(
+        &self,
+        src_eid: u8,
+        dst_eid: u8,
+        msg_tag: u8,
+        payload: Bytes,
+        mtu: usize,
+    ) -> Vec<Bytes> {
+        let hdr = TransportHeader::builder()
+            .src_eid(src_eid)
+            .dst_eid(dst_eid)
+            .msg_tag(msg_tag)
+            .tag_owner(true)
+            .build();
+        packetize(hdr, integrity::append_mic(payload), mtu)
+    }
+
+    /// Allocates the tag of a request from `src_eid` to `dst_eid`. When all 8 tags are in use,
+    /// waits for one to be released or to expire.
+    async fn allocate_tag(&self, src_eid: u8, dst_eid: u8) -> u8 {
+        loop {
+            let released = self.tag_released.notified();
+            let expires = match self.tags.lock().unwrap().allocate(src_eid, dst_eid) {
+                Ok(tag) => return tag,
+                Err(expires) => expires,
+            };
+            event!(
+                Level::DEBUG,
+                "all tags to EID {dst_eid:#04x} in use, waiting for one"
+            );
+            tokio::select! {
+                _ = released => {}
+                _ = tokio::time::sleep_until(expires) => {}
+            }
+        }
+    }
+
+    /// Stops waiting for the response to the request from `src_eid` to `dst_eid` with `msg_tag`.
+    fn remove_flow(&self, src_eid: u8, dst_eid: u8, msg_tag: u8) {
+        self.flows.lock().unwrap().retain(|(tag, _)| {
+            tag.msg_tag != msg_tag || tag.dest_eid != dst_eid || tag.src_eid != src_eid
+        });
+    }
+
+    fn release_tag(&self, src_eid: u8, dst_eid: u8, msg_tag: u8) {
+        self.tags.lock().unwrap().release(src_eid, dst_eid, msg_tag);
+        self.tag_released.notify_waiters();
+    }
+
+    /// Largest packet payload to send to `eid` out of `binding_id`: the MTU of the route to the
+    /// EID, bounded by the MTU of the binding.
+    fn mtu(&self, net: u32, eid: u8, binding_id: BindingDescriptor) -> usize {
+        let link_mtu = self
+            .binding_info(binding_id)
+            .map_or(MCTP_BASELINE_MTU, |info| info.mtu);
+        let mtu = match self.route_lookup(net, eid) {
+            Some(route) => route.mtu().min(link_mtu),
+            None => link_mtu,
+        };
+        mtu as usize
+    }
+
+    /// Sends the packets of a message in order, keeping to the rate limit negotiated with `eid`.
+    async fn transmit(
+        &self,
+        net: u32,
+        eid: u8,
+        binding_id: BindingDescriptor,
+        phy_addr: u64,
+        packets: Vec<Bytes>,
+    ) -> MctpEmuEmptyResult {
+        let binding_handle = self.get_binding(binding_id)?;
+        for packet in packets {
+            self.throttle(net, eid).await;
+            binding_handle.lock().await.transmit(packet, phy_addr)?;
+        }
+        Ok(())
+    }
+
+    /// Verifies and removes the MIC of a received message (transport header included). Messages
+    /// failing the check are counted and dropped.
+    fn check_integrity(&self, mut buf: Bytes) -> Option<Bytes> {
+        let hdr_size = std::mem::size_of::<TransportHeader>();
+        if buf.len() <= hdr_size {
+            return Some(buf);
+        }
+        let msg = buf.split_off(hdr_size);
+        match integrity::strip_mic(msg) {
+            Some(msg) => {
+                let mut checked = BytesMut::from(&buf[..]);
+                checked.put(msg);
+                Some(checked.freeze())
+            }
+            None => {
+                self.stats.lock().unwrap().mic_mismatches += 1;
+                tracing::warn!("MIC mismatch, dropping message");
+                None
+            }
+        }
+    }
+
+    /// Network, EID, binding and physical address to send to when targeting `addr`
+    fn next_hop(&self, addr: SocketAddress) -> MctpEmuResult<(u32, u8, BindingDescriptor, u64)> {
+        match addr {
+            SocketAddress::Extended {
+                address,
+                network,
+                binding_id,
+                phy_addr,
+            } => Ok((network, address, binding_id, phy_addr)),
+            SocketAddress::Basic { address, .. } => {
+                let (_, neighbour) =
+                    self.resolve(MCTP_NET_DEFAULT, address)
+                        .ok_or(Error::RouteNotFoundError {
+                            net: MCTP_NET_DEFAULT,
+                            eid: address,
+                        })?;
+                Ok((
+                    MCTP_NET_DEFAULT,
+                    address,
+                    neighbour.binding_id(),
+                    neighbour.phy_addr(),
+                ))
+            }
+        }
+    }
+
+    /// Hands a message that isn't part of a flow to the client it is addressed to, or to every
+    /// client of its message type when sent to the null or broadcast EID.
+    fn deliver_to_client(&self, dest_eid: u8, response: ClientCallbackMsg) {
+        let msg_type = match &response {
+            ClientCallbackMsg::Receive { buf, .. } if buf.len() > 4 => buf[4] & 0x7f,
+            _ => {
+                tracing::warn!("dropping message without a message type");
+                return;
+            }
+        };
+        let clients = self.clients.read().unwrap();
+        let mut recipients = clients
+            .values()
+            .filter(|client| client.read().unwrap().accepts(dest_eid, msg_type))
+            .peekable();
+        if recipients.peek().is_none() {
+            tracing::warn!(
+                "no client for message type {msg_type:#04x} to EID {dest_eid:#04x}, dropping message"
+            );
+            return;
+        }
+        let every_client = dest_eid == 0 || dest_eid == MCTP_ADDR_BCAST;
+        for client in recipients {
+            let sender_chan = client.read().unwrap().sender_chan.clone();
+            if sender_chan.try_send(response.clone()).is_err() {
+                tracing::warn!("client queue is full, dropping message");
+            }
+            if !every_client {
+                break;
+            }
+        }
+    }
+
+    /// Waits until the transmit rate limit negotiated with `eid` allows sending another packet.
+    async fn throttle(&self, net: u32, eid: u8) {
+        let wait = self.tx_limiters.reserve(net, eid);
+        if !wait.is_zero() {
+            event!(
+                Level::DEBUG,
+                "rate limited, delaying packet to EID {eid:#04x} by {wait:?}"
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Records where a peer EID was heard from so it can later be addressed by EID alone.
+    fn learn_neighbour(&self, eid: u8, binding_id: BindingDescriptor, phy_addr: u8) {
+        if eid == 0
+            || eid == MCTP_ADDR_BCAST
+            || self.neighbour_lookup(MCTP_NET_DEFAULT, eid).is_some()
+        {
+            return;
+        }
+        self.add_neighbour(Neighbour::new(
+            eid,
+            MCTP_NET_DEFAULT,
+            binding_id,
+            NeighbourSource::Discover,
+            &[phy_addr],
+        ));
+        if self.route_lookup(MCTP_NET_DEFAULT, eid).is_none() {
+            self.add_route(Route::new(
+                eid,
+                eid,
+                MCTP_NET_DEFAULT,
+                MCTP_BASELINE_MTU,
+                binding_id,
+                None,
+            ));
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -218,66 +484,73 @@ impl MctpNetwork for SimpleNetwork {
         payload: Bytes,
         addr: SocketAddress,
     ) -> MctpEmuResult<(SocketAddress, Bytes)> {
-        let (network, address, binding_id, phy_addr) = if let SocketAddress::Extended {
-            address,
-            network,
-            binding_id,
-            phy_addr,
-        } = addr
-        {
-            (network, address, binding_id, phy_addr)
-        } else {
-            todo!("Support looking up routes to get the physical address");
-        };
+        let options = self.get_client(sd)?.read().unwrap().request_options;
+        self.sendto_with_options(sd, payload, addr, options).await
+    }
 
-        let binding_handle = self.get_binding(binding_id)?;
+    async fn sendto_with_options(
+        &self,
+        sd: int32_t,
+        payload: Bytes,
+        addr: SocketAddress,
+        options: RequestOptions,
+    ) -> MctpEmuResult<(SocketAddress, Bytes)> {
+        let (network, address, binding_id, phy_addr) = self.next_hop(addr)?;
 
-        let mut buf = BytesMut::new();
+        let src_eid = self.get_client(sd)?.read().unwrap().address;
 
-        // scoping to allow grabbing mutex around client and then releasing it when finished
-        {
-            let client_handle = self.get_client(sd)?;
-            let client = client_handle.read().unwrap();
-            let Client {
-                address: client_address,
-                msg_type: client_msg_type,
-                tag: client_tag,
-                ..
-            } = client.deref();
+        let msg_tag = self.allocate_tag(src_eid, address).await;
+        let mtu = self.mtu(network, address, binding_id);
+        let packets = self.build_packets(src_eid, address, msg_tag, payload, mtu);
 
-            // TODO: support multiple packet messages (payload > MTU)
-            let hdr = TransportHeader::builder()
-                .src_eid(*client_address)
-                .dst_eid(address)
-                .msg_tag(*client_tag)
-                .tag_owner(true)
-                .start_of_msg(true)
-                .end_of_msg(true)
-                .build();
-            drop(client);
+        let (resp_tx, mut resp_rx) = oneshot::channel::<ClientCallbackMsg>();
 
-            buf.put(Bytes::from(hdr));
+        let tag = MsgFlowTag {
+            dest_eid: address,
+            src_eid,
+            msg_tag,
+            tag_owner: true,
+        };
+        {
+            // flows of requests that were abandoned (e.g. cancelled) don't wait for anything
+            let mut flows = self.flows.lock().unwrap();
+            flows.retain(|(_, resp)| !resp.is_closed());
+            flows.push((tag, resp_tx));
         }
-        buf.put(payload);
 
-        // TODO: create channel
-        let (resp_tx, resp_rx) = oneshot::channel::<ClientCallbackMsg>();
-
-        // TODO: allocate tag and track flow
-        let buf = buf.freeze();
-        match create_tag(buf.clone()) {
-            None => return Err(Error::Other(anyhow!("failed to allocate tag")).into()),
-            Some(tag) => {
-                self.flows.lock().unwrap().push((tag, resp_tx));
+        let mut retries = 0u8;
+        let response = loop {
+            let transmitted = self
+                .transmit(network, address, binding_id, phy_addr, packets.clone())
+                .await;
+            if transmitted.is_err() {
+                self.remove_flow(src_eid, address, msg_tag);
+                self.release_tag(src_eid, address, msg_tag);
+                transmitted?;
             }
-        }
-
-        // TODO: transmit message
-        let binding = binding_handle.lock().await;
-        binding.deref().transmit(buf.clone(), phy_addr).unwrap();
 
-        // TODO: wait for response
-        let res_bytes = resp_rx.await.map_err(|e| {
+            match tokio::time::timeout(options.timeout, &mut resp_rx).await {
+                Ok(response) => break response,
+                Err(_) if retries < options.retries => {
+                    retries += 1;
+                    event!(
+                        Level::DEBUG,
+                        "no response from EID {address:#04x}, resending request"
+                    );
+                    self.tags.lock().unwrap().renew(src_eid, address, msg_tag);
+                }
+                Err(_) => {
+                    // the tag stays allocated until it expires, a late response may still use it
+                    self.remove_flow(src_eid, address, msg_tag);
+                    return Err(Error::TimeoutError {
+                        eid: address,
+                        attempts: retries as u16 + 1,
+                    }
+                    .into());
+                }
+            }
+        };
+        let res_bytes = response.map_err(|e| {
             MctpEmuError::Network(Error::Other(anyhow!("response failed: {:?}", e)))
         })?;
 
@@ -289,11 +562,122 @@ impl MctpNetwork for SimpleNetwork {
         }
     }
 
+    async fn recvfrom(&self, sd: int32_t) -> MctpEmuResult<(SocketAddress, Bytes)> {
+        let client = self.get_client(sd)?;
+        match Client::receive(&client).await {
+            Some(ClientCallbackMsg::Receive { addr, buf }) => Ok((addr, buf)),
+            None => Err(Error::InvalidSocketError { sd }.into()),
+        }
+    }
+
+    async fn send_message(&self, mut buf: Bytes, addr: SocketAddress) -> MctpEmuEmptyResult {
+        let (network, address, binding_id, phy_addr) = self.next_hop(addr)?;
+        let hdr = TransportHeader::try_from(buf.clone())?;
+        let msg = buf.split_off(std::mem::size_of::<TransportHeader>());
+        let mtu = self.mtu(network, address, binding_id);
+        let packets = packetize(hdr, integrity::append_mic(msg), mtu);
+        self.transmit(network, address, binding_id, phy_addr, packets)
+            .await
+    }
+
+    fn set_address(&self, sd: int32_t, address: u8) -> MctpEmuResult<()> {
+        let client = self.get_client(sd)?;
+        client.write().unwrap().address = address;
+        Ok(())
+    }
+
+    fn set_request_options(&self, sd: int32_t, options: RequestOptions) -> MctpEmuResult<()> {
+        let client = self.get_client(sd)?;
+        client.write().unwrap().request_options = options;
+        Ok(())
+    }
+
+    async fn broadcast(
+        &self,
+        sd: int32_t,
+        payload: Bytes,
+        addr: SocketAddress,
+        timeout: Duration,
+    ) -> MctpEmuResult<Vec<(SocketAddress, Bytes)>> {
+        let (binding_id, phy_addr) = match addr {
+            SocketAddress::Extended {
+                binding_id,
+                phy_addr,
+                ..
+            } => (binding_id, phy_addr),
+            SocketAddress::Basic { .. } => {
+                return Err(Error::Other(anyhow!("broadcasts need an extended address")).into())
+            }
+        };
+
+        let src_eid = self.get_client(sd)?.read().unwrap().address;
+
+        let msg_tag = self.allocate_tag(src_eid, MCTP_ADDR_BCAST).await;
+        let mtu = self.mtu(MCTP_NET_DEFAULT, MCTP_ADDR_BCAST, binding_id);
+        let packets = self.build_packets(src_eid, MCTP_ADDR_BCAST, msg_tag, payload, mtu);
+
+        let (resp_tx, mut resp_rx) = mpsc::channel::<ClientCallbackMsg>(32);
+        let tag = MsgFlowTag {
+            dest_eid: MCTP_ADDR_BCAST,
+            src_eid,
+            msg_tag,
+            tag_owner: true,
+        };
+        self.broadcast_flows.lock().unwrap().push((tag, resp_tx));
+
+        let transmitted = self
+            .transmit(
+                MCTP_NET_DEFAULT,
+                MCTP_ADDR_BCAST,
+                binding_id,
+                phy_addr,
+                packets,
+            )
+            .await;
+
+        let mut responses = Vec::new();
+        if transmitted.is_ok() {
+            let deadline = tokio::time::Instant::now() + timeout;
+            while let Ok(Some(ClientCallbackMsg::Receive { addr, buf })) =
+                tokio::time::timeout_at(deadline, resp_rx.recv()).await
+            {
+                responses.push((addr, buf));
+            }
+        }
+
+        self.broadcast_flows
+            .lock()
+            .unwrap()
+            .retain(|(tag, _)| tag.msg_tag != msg_tag || !tag.tag_owner);
+        self.release_tag(src_eid, MCTP_ADDR_BCAST, msg_tag);
+
+        transmitted?;
+        event!(
+            Level::INFO,
+            "received {} responses to broadcast",
+            responses.len()
+        );
+        Ok(responses)
+    }
+
     async fn add_physical_binding(&self, binding: NetworkBindingHandle) -> MctpEmuEmptyResult {
-        let handle = match binding.lock().await.bind(1, self.rx_callback.clone()) {
-            Ok(handle) => handle,
-            Err(err) => {
-                return Err(Error::Other(anyhow!("failed calling binding: {:?}", err)).into())
+        let bind_id = self.next_binding_id.fetch_add(1, Ordering::SeqCst);
+        {
+            self.phys_bindings
+                .write()
+                .unwrap()
+                .insert(bind_id, binding.clone());
+        }
+
+        let handle = {
+            let mut binding = binding.lock().await;
+            self.routing
+                .set_binding_info(bind_id, BindingInfo::of(binding.deref()));
+            match binding.bind(bind_id, self.rx_callback.clone()) {
+                Ok(handle) => handle,
+                Err(err) => {
+                    return Err(Error::Other(anyhow!("failed calling binding: {:?}", err)).into())
+                }
             }
         };
 
@@ -311,4 +695,386 @@ impl MctpNetwork for SimpleNetwork {
         }
         handles
     }
+
+    fn stats(&self) -> NetworkStats {
+        let mut reassembler = self.reassembler.lock().unwrap();
+        reassembler.expire();
+        NetworkStats {
+            reassembly: reassembler.stats(),
+            ..*self.stats.lock().unwrap()
+        }
+    }
+
+    fn add_route(&self, route: Route) {
+        self.routing.add_route(route)
+    }
+
+    fn add_neighbour(&self, neighbour: Neighbour) {
+        self.routing.add_neighbour(neighbour)
+    }
+
+    fn network_id(&self) -> Uuid {
+        self.network_id
+    }
+
+    fn binding_info(&self, binding_id: BindingDescriptor) -> Option<BindingInfo> {
+        self.routing.binding_info(binding_id)
+    }
+
+    fn bindings(&self) -> Vec<(BindingDescriptor, BindingInfo)> {
+        self.routing.bindings()
+    }
+
+    fn set_tx_rate_limit(&self, dnet: uint32_t, eid: uint8_t, limit: Option<RateLimit>) {
+        self.tx_limiters.set(dnet, eid, limit)
+    }
+
+    fn tx_rate_limit(&self, dnet: uint32_t, eid: uint8_t) -> Option<RateLimit> {
+        self.tx_limiters.limit(dnet, eid)
+    }
+
+    fn routes(&self, dnet: uint32_t) -> Vec<RouteHandle> {
+        self.routing.routes(dnet)
+    }
+
+    fn route_lookup(&self, dnet: uint32_t, daddr: uint8_t) -> Option<RouteHandle> {
+        self.routing.route_lookup(dnet, daddr)
+    }
+
+    fn neighbour_lookup(&self, dnet: uint32_t, daddr: uint8_t) -> Option<NeighbourHandle> {
+        self.routing.neighbour_lookup(dnet, daddr)
+    }
+
+    fn neighbour_lookup_by_hw_addr(
+        &self,
+        dnet: uint32_t,
+        binding_id: BindingDescriptor,
+        hw_addr: &[uint8_t],
+    ) -> Option<NeighbourHandle> {
+        self.routing
+            .neighbour_lookup_by_hw_addr(dnet, binding_id, hw_addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::loopback::LoopbackBinding;
+    use anyhow::Result;
+
+    const VENDOR_PCI: u8 = MessageType::VendorDefinedPCI as u8;
+
+    fn message(msg: &[u8]) -> Bytes {
+        let hdr = TransportHeader::builder()
+            .src_eid(0x08)
+            .dst_eid(0x20)
+            .msg_tag(1)
+            .tag_owner(true)
+            .start_of_msg(true)
+            .end_of_msg(true)
+            .build();
+        let mut buf = BytesMut::new();
+        buf.put(Bytes::from(hdr));
+        buf.put_slice(msg);
+        buf.freeze()
+    }
+
+    async fn new_network() -> Result<(
+        Arc<SimpleNetwork>,
+        Sender<NetworkBindingCallbackMsg>,
+        Receiver<Bytes>,
+    )> {
+        let (binding, rx) = LoopbackBinding::new();
+        let binding = Arc::new(tokio::sync::Mutex::new(binding));
+        let network = SimpleNetwork::new(binding.clone(), Uuid::new_v4())?;
+        network.add_physical_binding(binding.clone()).await?;
+        let rx_callback = binding.lock().await.rx_callback();
+        Ok((network, rx_callback, rx))
+    }
+
+    async fn inject(rx_callback: &Sender<NetworkBindingCallbackMsg>, msg: Bytes) -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.put(Bytes::from(SmbusPhysTransportHeader::new(
+            0x20,
+            0x10,
+            msg.len() as u8,
+        )));
+        buf.put(msg);
+        rx_callback
+            .send(NetworkBindingCallbackMsg::Receive {
+                id: 1,
+                buf: buf.freeze(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_message_integrity_check() -> Result<()> {
+        let (network, rx_callback, mut transmitted) = new_network().await?;
+        let sd = network.socket();
+        network.bind(sd, 0x20, VENDOR_PCI, 0)?;
+
+        // the MIC is appended on send
+        let body = [VENDOR_PCI | 0x80, 0x12, 0x34, 0x01];
+        let addr = SocketAddress::Extended {
+            address: 0x08,
+            network: MCTP_NET_DEFAULT,
+            binding_id: 1,
+            phy_addr: 0x10,
+        };
+        network.send_message(message(&body), addr).await?;
+        let sent = transmitted.recv().await.unwrap();
+        let mic = crc32c::crc32c(&body).to_le_bytes();
+        assert_eq!(&sent[4..8], &body);
+        assert_eq!(&sent[8..], &mic);
+
+        // and checked and removed on receive
+        let mut corrupted = sent.to_vec();
+        corrupted[6] ^= 0x01;
+        inject(&rx_callback, Bytes::from(corrupted)).await?;
+        inject(&rx_callback, sent).await?;
+        let (_, buf) = tokio::time::timeout(Duration::from_secs(1), network.recvfrom(sd)).await??;
+        assert_eq!(&buf[4..], &body);
+        assert_eq!(network.stats().mic_mismatches, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_large_message_is_packetized() -> Result<()> {
+        let (network, _, mut transmitted) = new_network().await?;
+        let addr = SocketAddress::Extended {
+            address: 0x08,
+            network: MCTP_NET_DEFAULT,
+            binding_id: 1,
+            phy_addr: 0x10,
+        };
+        let mut body = vec![0xa5; 200];
+        body[0] = VENDOR_PCI;
+        network.send_message(message(&body), addr).await?;
+
+        let mut received = Vec::new();
+        for (index, len) in [64, 64, 64, 8].into_iter().enumerate() {
+            let packet = transmitted.recv().await.unwrap();
+            let hdr = TransportHeader::try_from(packet.clone())?;
+            assert_eq!(packet.len(), 4 + len);
+            assert_eq!(hdr.som(), (index == 0) as u8);
+            assert_eq!(hdr.eom(), (index == 3) as u8);
+            assert_eq!(hdr.packet_seq(), index as u8);
+            received.extend_from_slice(&packet[4..]);
+        }
+        assert!(transmitted.try_recv().is_err());
+        assert_eq!(received, body);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_multiple_packet_message_is_reassembled() -> Result<()> {
+        let (network, rx_callback, _) = new_network().await?;
+        let sd = network.socket();
+        network.bind(sd, 0x20, VENDOR_PCI, 0)?;
+
+        let hdr = TransportHeader::try_from(message(&[]))?;
+        let mut body = vec![0x5a; 150];
+        body[0] = VENDOR_PCI;
+        let packets = packetize(hdr, Bytes::from(body.clone()), 64);
+        assert_eq!(packets.len(), 3);
+
+        // a sequence gap drops the message
+        inject(&rx_callback, packets[0].clone()).await?;
+        inject(&rx_callback, packets[2].clone()).await?;
+        for packet in packets {
+            inject(&rx_callback, packet).await?;
+        }
+        let (_, buf) = tokio::time::timeout(Duration::from_secs(1), network.recvfrom(sd)).await??;
+        assert_eq!(&buf[4..], &body);
+        let hdr = TransportHeader::try_from(buf)?;
+        assert_eq!((hdr.som(), hdr.eom()), (1, 1));
+        assert_eq!(network.stats().reassembly.sequence_errors, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_get_their_own_tags() -> Result<()> {
+        let (network, rx_callback, mut transmitted) = new_network().await?;
+        let sd = network.socket();
+        network.bind(sd, 0x20, VENDOR_PCI, 0)?;
+        let addr = SocketAddress::Extended {
+            address: 0x08,
+            network: MCTP_NET_DEFAULT,
+            binding_id: 1,
+            phy_addr: 0x10,
+        };
+
+        let requests: Vec<_> = (0..9u8)
+            .map(|id| {
+                let network = network.clone();
+                tokio::spawn(async move {
+                    let payload = Bytes::from(vec![VENDOR_PCI, id]);
+                    network.sendto(sd, payload, addr).await
+                })
+            })
+            .collect();
+
+        let mut in_flight = Vec::new();
+        for _ in 0..8 {
+            let packet = transmitted.recv().await.unwrap();
+            let hdr = TransportHeader::try_from(packet.clone())?;
+            in_flight.push((hdr.msg_tag(), packet[5]));
+        }
+        let mut tags: Vec<_> = in_flight.iter().map(|(tag, _)| *tag).collect();
+        tags.sort();
+        assert_eq!(tags, (0..8).collect::<Vec<_>>());
+
+        // the ninth request waits for a tag
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(transmitted.try_recv().is_err());
+
+        let respond = |msg_tag: u8, id: u8| {
+            let hdr = TransportHeader::builder()
+                .src_eid(0x08)
+                .dst_eid(0x20)
+                .msg_tag(msg_tag)
+                .start_of_msg(true)
+                .end_of_msg(true)
+                .build();
+            let mut buf = BytesMut::new();
+            buf.put(Bytes::from(hdr));
+            buf.put_slice(&[VENDOR_PCI, id]);
+            buf.freeze()
+        };
+        for (msg_tag, id) in in_flight.iter().rev() {
+            inject(&rx_callback, respond(*msg_tag, *id)).await?;
+        }
+        let packet = tokio::time::timeout(Duration::from_secs(1), transmitted.recv())
+            .await?
+            .unwrap();
+        let hdr = TransportHeader::try_from(packet.clone())?;
+        inject(&rx_callback, respond(hdr.msg_tag(), packet[5])).await?;
+
+        for (id, request) in requests.into_iter().enumerate() {
+            let (_, buf) = tokio::time::timeout(Duration::from_secs(1), request).await???;
+            assert_eq!(buf[5], id as u8);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_request_timeouts_and_retries() -> Result<()> {
+        let (network, rx_callback, mut transmitted) = new_network().await?;
+        let sd = network.socket();
+        network.bind(sd, 0x20, VENDOR_PCI, 0)?;
+        let addr = SocketAddress::Extended {
+            address: 0x08,
+            network: MCTP_NET_DEFAULT,
+            binding_id: 1,
+            phy_addr: 0x10,
+        };
+        let options = RequestOptions {
+            timeout: Duration::from_millis(50),
+            retries: 1,
+        };
+
+        // the retry is answered
+        let request = {
+            let network = network.clone();
+            tokio::spawn(async move {
+                let payload = Bytes::from_static(&[VENDOR_PCI, 0x01]);
+                network
+                    .sendto_with_options(sd, payload, addr, options)
+                    .await
+            })
+        };
+        let first = transmitted.recv().await.unwrap();
+        let retry = transmitted.recv().await.unwrap();
+        assert_eq!(first, retry);
+        let msg_tag = TransportHeader::try_from(retry)?.msg_tag();
+        let hdr = TransportHeader::builder()
+            .src_eid(0x08)
+            .dst_eid(0x20)
+            .msg_tag(msg_tag)
+            .start_of_msg(true)
+            .end_of_msg(true)
+            .build();
+        let mut response = BytesMut::new();
+        response.put(Bytes::from(hdr));
+        response.put_slice(&[VENDOR_PCI, 0x02]);
+        inject(&rx_callback, response.freeze()).await?;
+        let (_, buf) = request.await??;
+        assert_eq!(&buf[4..], &[VENDOR_PCI, 0x02]);
+
+        // the socket options apply to requests without their own
+        network.set_request_options(sd, options)?;
+        let payload = Bytes::from_static(&[VENDOR_PCI, 0x03]);
+        match network.sendto(sd, payload, addr).await {
+            Err(MctpEmuError::Network(Error::TimeoutError {
+                eid: 0x08,
+                attempts: 2,
+            })) => {}
+            res => panic!("expected a timeout, got {res:?}"),
+        }
+        assert!(network.flows.lock().unwrap().is_empty());
+
+        // the largest retry count runs out instead of wrapping around
+        let drain = tokio::spawn(async move { while transmitted.recv().await.is_some() {} });
+        let options = RequestOptions {
+            timeout: Duration::from_millis(1),
+            retries: u8::MAX,
+        };
+        let payload = Bytes::from_static(&[VENDOR_PCI, 0x04]);
+        match network
+            .sendto_with_options(sd, payload, addr, options)
+            .await
+        {
+            Err(MctpEmuError::Network(Error::TimeoutError {
+                eid: 0x08,
+                attempts: 256,
+            })) => {}
+            res => panic!("expected a timeout, got {res:?}"),
+        }
+        drain.abort();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_reaches_every_client() -> Result<()> {
+        let (network, rx_callback, _) = new_network().await?;
+        let first = network.socket();
+        network.bind(first, 0x20, VENDOR_PCI, 0)?;
+        let second = network.socket();
+        network.bind(second, 0x21, VENDOR_PCI, 0)?;
+
+        let hdr = TransportHeader::builder()
+            .src_eid(0x08)
+            .dst_eid(MCTP_ADDR_BCAST)
+            .msg_tag(1)
+            .tag_owner(true)
+            .start_of_msg(true)
+            .end_of_msg(true)
+            .build();
+        let mut msg = BytesMut::new();
+        msg.put(Bytes::from(hdr));
+        msg.put_slice(&[VENDOR_PCI, 0x01]);
+        inject(&rx_callback, msg.freeze()).await?;
+        for sd in [first, second] {
+            let (_, buf) =
+                tokio::time::timeout(Duration::from_secs(1), network.recvfrom(sd)).await??;
+            assert_eq!(&buf[4..], &[VENDOR_PCI, 0x01]);
+        }
+
+        // unicast messages only go to the client with the EID
+        inject(&rx_callback, message(&[VENDOR_PCI, 0x02])).await?;
+        let (_, buf) =
+            tokio::time::timeout(Duration::from_secs(1), network.recvfrom(first)).await??;
+        assert_eq!(&buf[4..], &[VENDOR_PCI, 0x02]);
+        let unicast = tokio::time::timeout(Duration::from_millis(50), network.recvfrom(second));
+        assert!(unicast.await.is_err());
+
+        Ok(())
+    }
 }
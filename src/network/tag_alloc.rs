@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::network::MCTP_TAG_EXPIRY;
+
+/// Message tags are 3 bits wide
+const MCTP_TAG_COUNT: usize = 8;
+
+/// Tags given out for the requests from one EID to another
+#[derive(Debug, Default)]
+struct TagPool {
+    /// When each tag was allocated, `None` for free tags
+    allocated: [Option<Instant>; MCTP_TAG_COUNT],
+    /// Tag tried first on the next allocation, so freed tags aren't reused right away
+    next: usize,
+}
+
+/// Hands out the message tags of requests, 8 for each source and destination EID pair. Tags go
+/// back to the pool when released (i.e. the response arrived) or once they expire.
+#[derive(Debug)]
+pub(crate) struct TagAllocator {
+    pools: HashMap<(u8, u8), TagPool>,
+    expiry: Duration,
+}
+
+impl Default for TagAllocator {
+    fn default() -> Self {
+        Self::new(MCTP_TAG_EXPIRY)
+    }
+}
+
+impl TagAllocator {
+    pub(crate) fn new(expiry: Duration) -> Self {
+        Self {
+            pools: Default::default(),
+            expiry,
+        }
+    }
+
+    /// Allocates a tag for a request from `src_eid` to `dst_eid`. When all of them are in use,
+    /// returns when the oldest one expires instead.
+    pub(crate) fn allocate(&mut self, src_eid: u8, dst_eid: u8) -> Result<u8, Instant> {
+        self.allocate_at(src_eid, dst_eid, Instant::now())
+    }
+
+    /// Restarts the expiry of an allocated tag, e.g. when its request is resent.
+    pub(crate) fn renew(&mut self, src_eid: u8, dst_eid: u8, tag: u8) {
+        if let Some(pool) = self.pools.get_mut(&(src_eid, dst_eid)) {
+            if let Some(allocated) = &mut pool.allocated[tag as usize % MCTP_TAG_COUNT] {
+                *allocated = Instant::now();
+            }
+        }
+    }
+
+    pub(crate) fn release(&mut self, src_eid: u8, dst_eid: u8, tag: u8) {
+        if let Some(pool) = self.pools.get_mut(&(src_eid, dst_eid)) {
+            pool.allocated[tag as usize % MCTP_TAG_COUNT] = None;
+        }
+    }
+
+    fn allocate_at(&mut self, src_eid: u8, dst_eid: u8, now: Instant) -> Result<u8, Instant> {
+        let expiry = self.expiry;
+        let pool = self.pools.entry((src_eid, dst_eid)).or_default();
+        for offset in 0..MCTP_TAG_COUNT {
+            let tag = (pool.next + offset) % MCTP_TAG_COUNT;
+            let free = match pool.allocated[tag] {
+                Some(allocated) => now.duration_since(allocated) >= expiry,
+                None => true,
+            };
+            if free {
+                pool.allocated[tag] = Some(now);
+                pool.next = (tag + 1) % MCTP_TAG_COUNT;
+                return Ok(tag as u8);
+            }
+        }
+        let oldest = pool.allocated.iter().flatten().min().copied();
+        Err(oldest.unwrap_or(now) + expiry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_allocation() {
+        let mut tags = TagAllocator::new(Duration::from_secs(1));
+        let now = Instant::now();
+
+        let allocated: Vec<_> = (0..8)
+            .map(|_| tags.allocate_at(0x08, 0x20, now).unwrap())
+            .collect();
+        assert_eq!(allocated, (0..8).collect::<Vec<_>>());
+        assert_eq!(
+            tags.allocate_at(0x08, 0x20, now),
+            Err(now + Duration::from_secs(1))
+        );
+        // every source and destination pair has its own tags
+        assert_eq!(tags.allocate_at(0x08, 0x21, now), Ok(0));
+
+        tags.release(0x08, 0x20, 5);
+        assert_eq!(tags.allocate_at(0x08, 0x20, now), Ok(5));
+
+        // tags whose response never came expire
+        let later = now + Duration::from_secs(1);
+        assert_eq!(tags.allocate_at(0x08, 0x20, later), Ok(6));
+    }
+}
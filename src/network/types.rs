@@ -17,14 +17,19 @@ use tokio::net::UdpSocket;
 use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::{mpsc, oneshot, Mutex};
 use tokio::task::JoinHandle;
+use uuid::Uuid;
 
 use crate::{MctpEmuEmptyResult, MctpEmuError, MctpEmuResult, OneshotResponder};
 use mctp_base_lib::control::enums::CompletionCode::Error;
 use mctp_base_lib::{
     base::*,
     control::{
-        enums::{CommandCode, CompletionCode, MessageType},
+        enums::{
+            CommandCode, CompletionCode, MessageType, PhysicalMediumIdentifier,
+            PhysicalTransportBinding,
+        },
         get_eid::{EidType, EndpointType},
+        models::RateLimit,
         ControlMsgReponseStatus, *,
     },
 };
@@ -41,6 +46,72 @@ pub trait NetworkBinding: Debug + Send + Sync {
         id: u64,
         rx_callback: Sender<NetworkBindingCallbackMsg>,
     ) -> MctpEmuResult<JoinHandle<MctpEmuEmptyResult>>;
+
+    /// Physical transport binding implemented by this binding
+    fn transport_binding(&self) -> PhysicalTransportBinding {
+        PhysicalTransportBinding::VendorDefined
+    }
+
+    /// Physical medium this binding transmits on
+    fn physical_medium(&self) -> PhysicalMediumIdentifier {
+        PhysicalMediumIdentifier::Unspecified
+    }
+
+    /// Largest transmission unit the link supports
+    fn mtu(&self) -> u32 {
+        MCTP_BASELINE_MTU
+    }
+}
+
+/// Physical layer details of a network binding, captured when it is added to a network.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BindingInfo {
+    pub transport_binding: PhysicalTransportBinding,
+    pub physical_medium: PhysicalMediumIdentifier,
+    pub mtu: u32,
+}
+
+impl BindingInfo {
+    pub fn of(binding: &dyn NetworkBinding) -> Self {
+        Self {
+            transport_binding: binding.transport_binding(),
+            physical_medium: binding.physical_medium(),
+            mtu: binding.mtu(),
+        }
+    }
+}
+
+impl Default for BindingInfo {
+    fn default() -> Self {
+        Self {
+            transport_binding: Default::default(),
+            physical_medium: Default::default(),
+            mtu: MCTP_BASELINE_MTU,
+        }
+    }
+}
+
+/// Counters of the messages a network dropped, e.g. for failing their integrity check.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NetworkStats {
+    /// Received messages whose MIC didn't match their contents
+    pub mic_mismatches: u64,
+    pub reassembly: ReassemblyStats,
+}
+
+/// Counters of the ways reassembling a multiple packet message failed
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReassemblyStats {
+    /// Packets without SOM received while no message was in progress for their tag
+    pub missing_som: u64,
+    /// Messages dropped because a packet sequence number was skipped or repeated
+    pub sequence_errors: u64,
+    /// Messages dropped for growing past [`MCTP_MAX_MESSAGE_SIZE`]
+    pub too_large: u64,
+    /// Messages dropped because their next packet didn't arrive within [`MCTP_REASSEMBLY_TIMEOUT`]
+    pub timeouts: u64,
+    /// Messages dropped because a new message was started with the same tag
+    pub interrupted: u64,
 }
 
 pub const MCTP_NET_ANY: u8 = 0x08;
@@ -48,6 +119,47 @@ pub const MCTP_ADDR_ANY: u8 = 0x08;
 pub const MCTP_ADDR_BCAST: u8 = 0xff;
 pub const MCTP_TAG_OWNER: u8 = 0x08;
 
+/// Network used for addresses that don't name one (e.g. [`SocketAddress::Basic`])
+pub const MCTP_NET_DEFAULT: u32 = 1;
+
+/// Baseline transmission unit size every MCTP medium has to support
+pub const MCTP_BASELINE_MTU: u32 = 64;
+
+/// Time allowed between two packets of a message before its reassembly is abandoned
+pub const MCTP_REASSEMBLY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Largest message a network reassembles, MIC included
+pub const MCTP_MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// Time after which the tag of a request that got no response can be reused (MT4 in DSP0236)
+pub const MCTP_TAG_EXPIRY: Duration = Duration::from_secs(6);
+
+/// Time [`MctpNetwork::sendto`] waits for a response by default
+pub const MCTP_REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Number of times [`MctpNetwork::sendto`] resends an unanswered request by default
+pub const MCTP_REQUEST_RETRIES: u8 = 2;
+
+/// How long a request waits for its response and how many times it is resent when none arrives.
+/// Retries resend the request unchanged, keeping its tag and (for control messages) its instance
+/// ID, so a late response to an earlier attempt answers the request too.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RequestOptions {
+    /// Time to wait for the response to each attempt
+    pub timeout: Duration,
+    /// Number of times the request is resent after a timeout
+    pub retries: u8,
+}
+
+impl Default for RequestOptions {
+    fn default() -> Self {
+        Self {
+            timeout: MCTP_REQUEST_TIMEOUT,
+            retries: MCTP_REQUEST_RETRIES,
+        }
+    }
+}
+
 #[derive(Copy, Clone, BitfieldStruct, Debug, PartialEq, Eq, Default)]
 #[repr(C, packed)]
 pub struct MctpAddr {
@@ -91,7 +203,7 @@ pub enum SocketAddress {
     },
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum ClientCallbackMsg {
     Receive { addr: SocketAddress, buf: Bytes },
 }
@@ -100,9 +212,13 @@ pub enum ClientCallbackMsg {
 pub struct Client {
     pub address: u8,
     pub msg_type: u8,
+    /// Tag the client was bound with. Requests sent through `SimpleNetwork` get theirs from its
+    /// tag allocator instead.
     pub tag: u8,
+    /// Options of the requests sent through the socket without options of their own
+    pub request_options: RequestOptions,
     pub sender_chan: Sender<ClientCallbackMsg>,
-    receive_chan: Receiver<ClientCallbackMsg>,
+    receive_chan: Arc<Mutex<Receiver<ClientCallbackMsg>>>,
 }
 
 impl Client {
@@ -113,11 +229,26 @@ impl Client {
             address,
             msg_type,
             tag,
+            request_options: Default::default(),
             sender_chan: sender,
-            receive_chan: receiver,
+            receive_chan: Arc::new(Mutex::new(receiver)),
         };
         Arc::new(RwLock::new(client))
     }
+
+    /// Whether a message of `msg_type` sent to `dest_eid` is for this client. Messages to the null
+    /// and broadcast EIDs are delivered to every client of the message type.
+    pub(crate) fn accepts(&self, dest_eid: u8, msg_type: u8) -> bool {
+        self.msg_type == msg_type
+            && (dest_eid == self.address || dest_eid == 0 || dest_eid == MCTP_ADDR_BCAST)
+    }
+
+    /// Waits for the next message delivered to the client
+    pub(crate) async fn receive(client: &ClientHandle) -> Option<ClientCallbackMsg> {
+        let receive_chan = client.read().unwrap().receive_chan.clone();
+        let mut receiver = receive_chan.lock().await;
+        receiver.recv().await
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -127,13 +258,81 @@ pub enum NeighbourSource {
     Discover = 1,
 }
 
+/// Maps an EID to the physical address used to reach it on one of the network bindings.
 #[derive(Debug)]
 pub struct Neighbour {
     eid: uint8_t,
+    net: uint32_t,
+    binding_id: BindingDescriptor,
     source: NeighbourSource,
     ha: [uint8_t; 32],
+    ha_len: uint8_t,
+}
+
+impl Neighbour {
+    pub fn new(
+        eid: uint8_t,
+        net: uint32_t,
+        binding_id: BindingDescriptor,
+        source: NeighbourSource,
+        hw_addr: &[uint8_t],
+    ) -> Self {
+        let mut ha = [0; 32];
+        let ha_len = hw_addr.len().min(ha.len());
+        ha[..ha_len].copy_from_slice(&hw_addr[..ha_len]);
+        Self {
+            eid,
+            net,
+            binding_id,
+            source,
+            ha,
+            ha_len: ha_len as uint8_t,
+        }
+    }
+
+    pub fn eid(&self) -> uint8_t {
+        self.eid
+    }
+
+    pub fn net(&self) -> uint32_t {
+        self.net
+    }
+
+    pub fn binding_id(&self) -> BindingDescriptor {
+        self.binding_id
+    }
+
+    pub fn source(&self) -> &NeighbourSource {
+        &self.source
+    }
+
+    /// Medium specific hardware address (e.g. the 7-bit SMBus address)
+    pub fn hw_addr(&self) -> &[uint8_t] {
+        &self.ha[..self.ha_len as usize]
+    }
+
+    /// Hardware address in the form expected by [`NetworkBinding::transmit`]
+    pub fn phy_addr(&self) -> u64 {
+        self.hw_addr()
+            .iter()
+            .fold(0u64, |addr, byte| (addr << 8) | *byte as u64)
+    }
+
+    pub(crate) fn matches(&self, dnet: uint32_t, daddr: uint8_t) -> bool {
+        dnet == self.net && self.eid == daddr
+    }
+
+    pub(crate) fn matches_hw_addr(
+        &self,
+        dnet: uint32_t,
+        binding_id: BindingDescriptor,
+        hw_addr: &[uint8_t],
+    ) -> bool {
+        dnet == self.net && self.binding_id == binding_id && self.hw_addr() == hw_addr
+    }
 }
 
+/// Routes a range of EIDs out of a binding, either directly or through a bridge (gateway).
 #[derive(Debug)]
 pub struct Route {
     min_eid: uint8_t,
@@ -141,12 +340,71 @@ pub struct Route {
     net: uint32_t,
     mtu: uint32_t,
     route_type: uint8_t,
+    binding_id: BindingDescriptor,
+    gateway_eid: Option<uint8_t>,
 }
 
 impl Route {
+    /// Creates a route for `min_eid..=max_eid`. An MTU below the baseline is raised to it, since
+    /// every medium has to carry baseline sized packets.
+    pub fn new(
+        min_eid: uint8_t,
+        max_eid: uint8_t,
+        net: uint32_t,
+        mtu: uint32_t,
+        binding_id: BindingDescriptor,
+        gateway_eid: Option<uint8_t>,
+    ) -> Self {
+        Self {
+            min_eid,
+            max_eid,
+            net,
+            mtu: mtu.max(MCTP_BASELINE_MTU),
+            route_type: 0,
+            binding_id,
+            gateway_eid,
+        }
+    }
+
+    pub fn min_eid(&self) -> uint8_t {
+        self.min_eid
+    }
+
+    pub fn max_eid(&self) -> uint8_t {
+        self.max_eid
+    }
+
+    /// Number of EIDs in the range, as reported in routing table entries. A range covering every
+    /// EID has 256, which the one byte field can't hold, so it is reported as 255.
+    pub fn eid_range_size(&self) -> uint8_t {
+        let size = self.max_eid as u16 - self.min_eid as u16 + 1;
+        size.min(u8::MAX as u16) as uint8_t
+    }
+
+    pub fn net(&self) -> uint32_t {
+        self.net
+    }
+
+    pub fn mtu(&self) -> uint32_t {
+        self.mtu
+    }
+
+    pub fn binding_id(&self) -> BindingDescriptor {
+        self.binding_id
+    }
+
+    /// EID of the bridge used to reach this range, `None` when the EIDs are on the local bus
+    pub fn gateway_eid(&self) -> Option<uint8_t> {
+        self.gateway_eid
+    }
+
     pub(crate) fn matches(&self, dnet: uint32_t, daddr: uint8_t) -> bool {
         dnet == self.net && self.min_eid <= daddr && self.max_eid >= daddr
     }
+
+    pub(crate) fn same_range(&self, other: &Route) -> bool {
+        self.net == other.net && self.min_eid == other.min_eid && self.max_eid == other.max_eid
+    }
 }
 
 pub trait NetDevice {
@@ -155,9 +413,10 @@ pub trait NetDevice {
 }
 
 #[async_trait::async_trait]
-pub trait MctpNetwork {
+pub trait MctpNetwork: Send + Sync {
     fn socket(&self) -> i32;
     fn bind(&self, sd: i32, address: u8, msg_type: u8, tag: u8) -> MctpEmuResult<()>;
+    /// Sends a request and waits for its response, using the request options of the socket.
     async fn sendto(
         &self,
         sd: i32,
@@ -165,9 +424,83 @@ pub trait MctpNetwork {
         addr: SocketAddress,
     ) -> MctpEmuResult<(SocketAddress, Bytes)>;
 
+    /// Sends a request and waits for its response, resending it on timeout as told by `options`.
+    async fn sendto_with_options(
+        &self,
+        sd: i32,
+        payload: Bytes,
+        addr: SocketAddress,
+        options: RequestOptions,
+    ) -> MctpEmuResult<(SocketAddress, Bytes)>;
+
+    /// Changes the options of the requests sent through `sd` with [`MctpNetwork::sendto`].
+    fn set_request_options(&self, sd: i32, options: RequestOptions) -> MctpEmuResult<()>;
+
+    /// Waits for the next message that isn't a response to a request of the client bound to `sd`,
+    /// e.g. a request from another endpoint.
+    async fn recvfrom(&self, sd: i32) -> MctpEmuResult<(SocketAddress, Bytes)>;
+
+    /// Transmits a message that already starts with its transport header (e.g. a response) without
+    /// waiting for anything in return.
+    async fn send_message(&self, buf: Bytes, addr: SocketAddress) -> MctpEmuEmptyResult;
+
+    /// Changes the EID of the client bound to `sd`, e.g. after Set Endpoint ID.
+    fn set_address(&self, sd: i32, address: u8) -> MctpEmuResult<()>;
+
+    /// Sends `payload` to the broadcast EID out of the binding named by the extended `addr` and
+    /// collects every response received before `timeout` expires.
+    async fn broadcast(
+        &self,
+        sd: i32,
+        payload: Bytes,
+        addr: SocketAddress,
+        timeout: Duration,
+    ) -> MctpEmuResult<Vec<(SocketAddress, Bytes)>>;
+
     async fn add_physical_binding(&self, binding: NetworkBindingHandle) -> MctpEmuEmptyResult;
 
     fn join_handles(&self) -> Vec<JoinHandle<MctpEmuEmptyResult>>;
+
+    /// Counters of the messages dropped by the network so far
+    fn stats(&self) -> NetworkStats;
+
+    /// Identity of the network, reported by the endpoints attached to it through Get Network ID
+    fn network_id(&self) -> Uuid;
+
+    /// Physical layer details of a binding that was added to the network
+    fn binding_info(&self, binding_id: BindingDescriptor) -> Option<BindingInfo>;
+    /// Every binding added to the network, ordered by binding descriptor
+    fn bindings(&self) -> Vec<(BindingDescriptor, BindingInfo)>;
+
+    /// Limits the rate packets are sent to `eid` at, as negotiated through Request TX Rate Limit.
+    /// `None` or an unlimited rate removes the limit.
+    fn set_tx_rate_limit(&self, dnet: uint32_t, eid: uint8_t, limit: Option<RateLimit>);
+    /// Limit currently applied to the packets sent to `eid`
+    fn tx_rate_limit(&self, dnet: uint32_t, eid: uint8_t) -> Option<RateLimit>;
+
+    /// Adds a route, replacing any existing route for the same network and EID range.
+    fn add_route(&self, route: Route);
+    /// Adds a neighbour, replacing any existing neighbour for the same network and EID.
+    fn add_neighbour(&self, neighbour: Neighbour);
+    /// All routes for the network, in the order they were added.
+    fn routes(&self, dnet: uint32_t) -> Vec<RouteHandle>;
+    fn route_lookup(&self, dnet: uint32_t, daddr: uint8_t) -> Option<RouteHandle>;
+    fn neighbour_lookup(&self, dnet: uint32_t, daddr: uint8_t) -> Option<NeighbourHandle>;
+    fn neighbour_lookup_by_hw_addr(
+        &self,
+        dnet: uint32_t,
+        binding_id: BindingDescriptor,
+        hw_addr: &[uint8_t],
+    ) -> Option<NeighbourHandle>;
+
+    /// Finds the neighbour to send to when targeting `daddr`: the endpoint itself when it is on a
+    /// local bus, otherwise the bridge its route goes through.
+    fn resolve(&self, dnet: uint32_t, daddr: uint8_t) -> Option<(RouteHandle, NeighbourHandle)> {
+        let route = self.route_lookup(dnet, daddr)?;
+        let next_hop = route.gateway_eid().unwrap_or(daddr);
+        let neighbour = self.neighbour_lookup(dnet, next_hop)?;
+        Some((route, neighbour))
+    }
 }
 
 #[derive(Debug)]
@@ -189,4 +522,5 @@ pub type BindingDescriptor = u64;
 pub type NetworkBindingHandle = Arc<tokio::sync::Mutex<dyn NetworkBinding>>;
 pub type ClientHandle = Arc<RwLock<Client>>;
 pub type RouteHandle = Arc<Route>;
+pub type NeighbourHandle = Arc<Neighbour>;
 pub type MctpNetworkHandle = Arc<dyn MctpNetwork>;
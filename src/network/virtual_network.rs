@@ -11,7 +11,7 @@ use std::borrow::{Borrow, BorrowMut};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::io;
-use std::ops::Index;
+use std::ops::{Deref, Index};
 use std::sync::atomic::{AtomicI32, AtomicU16, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
@@ -20,27 +20,35 @@ use tokio::sync::mpsc::{Receiver, Sender};
 use tokio::sync::{mpsc, oneshot, MutexGuard};
 use tokio::task::JoinHandle;
 use tracing::{event, Level};
+use uuid::Uuid;
 
 use mctp_base_lib::{
     base::*,
     control::{
         enums::{CommandCode, CompletionCode, MessageType},
         get_eid::{EidType, EndpointType},
+        models::RateLimit,
         ControlMsgReponseStatus, *,
     },
 };
 
 use crate::{
-    network::{types::*, Error, NetDevice, Result},
+    network::{
+        rate_limit::TxRateLimiters, routing::RoutingTable, types::*, Error, NetDevice, Result,
+    },
     MctpEmuEmptyResult, MctpEmuResult,
 };
 
 #[derive(Debug, derive_builder::Builder, smart_default::SmartDefault)]
 #[builder(private, pattern = "owned", default)]
 pub struct VirtualNetwork {
+    #[default(_code = "Uuid::new_v4()")]
+    network_id: Uuid,
     clients: Arc<RwLock<HashMap<i32, ClientHandle>>>,
     num_clients: AtomicI32,
-    routes: Arc<RwLock<Vec<Arc<Route>>>>,
+    routing: Arc<RoutingTable>,
+    tx_limiters: Arc<TxRateLimiters>,
+    stats: Arc<Mutex<NetworkStats>>,
     net_devs: Arc<RwLock<Vec<NetworkBindingHandle>>>,
     num_bindings: AtomicU64,
     callback_handles: Arc<RwLock<Vec<JoinHandle<MctpEmuEmptyResult>>>>,
@@ -50,14 +58,21 @@ pub struct VirtualNetwork {
 
 impl VirtualNetwork {
     pub fn new_mctp_network() -> MctpEmuResult<MctpNetworkHandle> {
-        let network = VirtualNetwork::new()?;
+        Self::new_mctp_network_with_id(Uuid::new_v4())
+    }
+
+    /// Creates a network with a fixed identity instead of a random one
+    pub fn new_mctp_network_with_id(network_id: Uuid) -> MctpEmuResult<MctpNetworkHandle> {
+        let network = VirtualNetwork::new(network_id)?;
         Ok(network)
     }
 
-    fn new() -> MctpEmuResult<Arc<Self>> {
+    fn new(network_id: Uuid) -> MctpEmuResult<Arc<Self>> {
         let (sender, mut receiver) = mpsc::channel::<NetworkBindingCallbackMsg>(32);
 
-        let builder = VirtualNetworkBuilder::default().rx_callback(sender);
+        let builder = VirtualNetworkBuilder::default()
+            .network_id(network_id)
+            .rx_callback(sender);
         let mut network: VirtualNetwork = match builder.build() {
             Ok(n) => n,
             Err(err) => {
@@ -102,7 +117,7 @@ impl VirtualNetwork {
     }
 
     fn get_binding(&self, binding_id: u64) -> Result<NetworkBindingHandle> {
-        if binding_id < (self.num_bindings.load(Ordering::SeqCst) as u64 - 1) {
+        if binding_id < (self.num_bindings.load(Ordering::SeqCst) - 1) {
             return Err(Error::InvalidBindingError { binding_id });
         }
         match self.net_devs.read().unwrap().get(binding_id as usize) {
@@ -118,15 +133,6 @@ impl VirtualNetwork {
         }
     }
 
-    fn route_lookup(&self, dnet: uint32_t, daddr: uint8_t) -> Option<Arc<Route>> {
-        for route in self.routes.read().unwrap().iter() {
-            if route.matches(dnet, daddr) {
-                return Some(route.clone());
-            }
-        }
-        None
-    }
-
     async fn mctp_tx_thread() {
         tokio::spawn(async move {
             loop {
@@ -154,10 +160,21 @@ impl MctpNetwork for VirtualNetwork {
     }
 
     async fn sendto(
+        &self,
+        sd: int32_t,
+        payload: Bytes,
+        addr: SocketAddress,
+    ) -> MctpEmuResult<(SocketAddress, Bytes)> {
+        let options = self.get_client(sd)?.read().unwrap().request_options;
+        self.sendto_with_options(sd, payload, addr, options).await
+    }
+
+    async fn sendto_with_options(
         &self,
         _sd: int32_t,
         payload: Bytes,
         addr: SocketAddress,
+        _options: RequestOptions,
     ) -> MctpEmuResult<(SocketAddress, Bytes)> {
         let (network, address, binding_id, phy_addr) = if let SocketAddress::Extended {
             address,
@@ -176,16 +193,58 @@ impl MctpNetwork for VirtualNetwork {
         todo!()
     }
 
+    async fn recvfrom(&self, sd: int32_t) -> MctpEmuResult<(SocketAddress, Bytes)> {
+        let client = self.get_client(sd)?;
+        match Client::receive(&client).await {
+            Some(ClientCallbackMsg::Receive { addr, buf }) => Ok((addr, buf)),
+            None => Err(Error::InvalidSocketError { sd }.into()),
+        }
+    }
+
+    async fn send_message(&self, _buf: Bytes, _addr: SocketAddress) -> MctpEmuEmptyResult {
+        Err(Error::Other(anyhow!(
+            "sending messages isn't supported by VirtualNetwork yet"
+        ))
+        .into())
+    }
+
+    fn set_address(&self, sd: int32_t, address: u8) -> MctpEmuResult<()> {
+        let client = self.get_client(sd)?;
+        client.write().unwrap().address = address;
+        Ok(())
+    }
+
+    fn set_request_options(&self, sd: int32_t, options: RequestOptions) -> MctpEmuResult<()> {
+        let client = self.get_client(sd)?;
+        client.write().unwrap().request_options = options;
+        Ok(())
+    }
+
+    async fn broadcast(
+        &self,
+        _sd: int32_t,
+        _payload: Bytes,
+        _addr: SocketAddress,
+        _timeout: Duration,
+    ) -> MctpEmuResult<Vec<(SocketAddress, Bytes)>> {
+        Err(Error::Other(anyhow!("broadcasts aren't supported by VirtualNetwork yet")).into())
+    }
+
     async fn add_physical_binding(&self, binding: NetworkBindingHandle) -> MctpEmuEmptyResult {
         let bind_id = self.num_bindings.fetch_add(1, Ordering::SeqCst);
         {
             self.net_devs.write().unwrap().push(binding.clone());
         }
 
-        let handle = match binding.lock().await.bind(bind_id, self.rx_callback.clone()) {
-            Ok(handle) => handle,
-            Err(err) => {
-                return Err(Error::Other(anyhow!("failed calling binding: {:?}", err)).into())
+        let handle = {
+            let mut binding = binding.lock().await;
+            self.routing
+                .set_binding_info(bind_id, BindingInfo::of(binding.deref()));
+            match binding.bind(bind_id, self.rx_callback.clone()) {
+                Ok(handle) => handle,
+                Err(err) => {
+                    return Err(Error::Other(anyhow!("failed calling binding: {:?}", err)).into())
+                }
             }
         };
 
@@ -203,4 +262,58 @@ impl MctpNetwork for VirtualNetwork {
         }
         handles
     }
+
+    fn stats(&self) -> NetworkStats {
+        *self.stats.lock().unwrap()
+    }
+
+    fn add_route(&self, route: Route) {
+        self.routing.add_route(route)
+    }
+
+    fn add_neighbour(&self, neighbour: Neighbour) {
+        self.routing.add_neighbour(neighbour)
+    }
+
+    fn network_id(&self) -> Uuid {
+        self.network_id
+    }
+
+    fn binding_info(&self, binding_id: BindingDescriptor) -> Option<BindingInfo> {
+        self.routing.binding_info(binding_id)
+    }
+
+    fn bindings(&self) -> Vec<(BindingDescriptor, BindingInfo)> {
+        self.routing.bindings()
+    }
+
+    fn set_tx_rate_limit(&self, dnet: uint32_t, eid: uint8_t, limit: Option<RateLimit>) {
+        self.tx_limiters.set(dnet, eid, limit)
+    }
+
+    fn tx_rate_limit(&self, dnet: uint32_t, eid: uint8_t) -> Option<RateLimit> {
+        self.tx_limiters.limit(dnet, eid)
+    }
+
+    fn routes(&self, dnet: uint32_t) -> Vec<RouteHandle> {
+        self.routing.routes(dnet)
+    }
+
+    fn route_lookup(&self, dnet: uint32_t, daddr: uint8_t) -> Option<RouteHandle> {
+        self.routing.route_lookup(dnet, daddr)
+    }
+
+    fn neighbour_lookup(&self, dnet: uint32_t, daddr: uint8_t) -> Option<NeighbourHandle> {
+        self.routing.neighbour_lookup(dnet, daddr)
+    }
+
+    fn neighbour_lookup_by_hw_addr(
+        &self,
+        dnet: uint32_t,
+        binding_id: BindingDescriptor,
+        hw_addr: &[uint8_t],
+    ) -> Option<NeighbourHandle> {
+        self.routing
+            .neighbour_lookup_by_hw_addr(dnet, binding_id, hw_addr)
+    }
 }
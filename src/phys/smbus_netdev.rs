@@ -12,7 +12,10 @@ use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
 use tracing::{event, Level};
 
-use mctp_base_lib::base::*;
+use mctp_base_lib::{
+    base::*,
+    control::enums::{PhysicalMediumIdentifier, PhysicalTransportBinding},
+};
 
 use crate::{
     hex_dump::print_buf,
@@ -60,7 +63,7 @@ async fn poll_socket(
 fn validate_smbus_address(addr: u64) -> MctpEmuEmptyResult {
     // Filter out reserved, invalid and unsupported addresses
     if addr < 8 || (addr >> 3) == 0b1111 || addr > 0x7F {
-        return Err(Error::InvalidAddress { addr: addr as u64 }.into());
+        return Err(Error::InvalidAddress { addr }.into());
     }
     Ok(())
 }
@@ -110,8 +113,13 @@ impl NetworkBinding for SmbusNetDevBinding {
     fn transmit(&self, msg: Bytes, phy_addr: u64) -> MctpEmuEmptyResult {
         tracing::info!("sending command to {phy_addr:?}");
         validate_smbus_address(phy_addr)?;
+        // the network splits messages into packets that fit the binding MTU
         if msg.len() >= 256 {
-            todo!("Support fragmenting messages");
+            return Err(Error::TransmitError(format!(
+                "packet too large for SMBus: {:?} bytes",
+                msg.len()
+            ))
+            .into());
         }
 
         let dest_addr: u8 = (phy_addr & 0x7f) as u8;
@@ -125,8 +133,6 @@ impl NetworkBinding for SmbusNetDevBinding {
         tx_buf.put_slice(&[pec(msg.as_ref())]);
 
         let socket = self.socket.as_ref().unwrap().clone();
-        let sent_bytes = socket.try_send(&tx_buf[..]);
-
         match socket.try_send(&tx_buf[..]) {
             Ok(sent_bytes) => {
                 if sent_bytes == tx_buf.len() {
@@ -159,6 +165,14 @@ impl NetworkBinding for SmbusNetDevBinding {
 
         Ok(handle)
     }
+
+    fn transport_binding(&self) -> PhysicalTransportBinding {
+        PhysicalTransportBinding::MCTPoverSMBus
+    }
+
+    fn physical_medium(&self) -> PhysicalMediumIdentifier {
+        PhysicalMediumIdentifier::SMBUS_2_0_100khz
+    }
 }
 
 #[cfg(test)]
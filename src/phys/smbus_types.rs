@@ -21,4 +21,8 @@ impl SmbusPhysTransportHeader {
             src_addr: src_addr_7bit << 1 | 0x01,
         }
     }
+
+    pub fn src_addr_7bit(&self) -> u8 {
+        self.src_addr >> 1
+    }
 }